@@ -47,8 +47,11 @@ clippy::suspicious,
 
 mod raycast;
 
-use vokselis::{run, Camera, Context, Demo, VolumeTexture};
 use raycast::RaycastPipeline;
+use vokselis::{
+    run, Camera, CameraBinding, CameraBookmarks, Context, ContextOptions, Demo, Error,
+    InteractiveLod, VolumeTexture,
+};
 
 use wgpu::StoreOp;
 use winit::{dpi::LogicalSize, event_loop::EventLoopBuilder, window::WindowBuilder};
@@ -61,16 +64,29 @@ struct Bonsai {
 }
 
 impl Demo for Bonsai {
-    fn init(ctx: &mut Context) -> Self {
+    fn options() -> ContextOptions {
+        ContextOptions::new()
+            .interactive_lod(InteractiveLod::default())
+            .taa(true)
+            .bookmarks(CameraBookmarks::path_for(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../../assets/bonsai_256x256x256_uint8.raw"
+            )))
+    }
+
+    fn init(ctx: &mut Context) -> Result<Self, Error> {
         let volume_texture = VolumeTexture::new(&ctx.device, &ctx.queue);
         let pipeline = {
-            let module_desc = wgpu::include_wgsl!("../../../shaders/raycast_naive.wgsl");
+            let module_desc = CameraBinding::shader_module_desc(
+                "raycast_naive.wgsl",
+                include_str!("../../../shaders/raycast_naive.wgsl"),
+            );
             RaycastPipeline::new(&ctx.device, module_desc)
         };
-        Self {
+        Ok(Self {
             volume_texture,
             pipeline,
-        }
+        })
     }
 
     fn render(&mut self, ctx: &Context) {
@@ -106,13 +122,13 @@ impl Demo for Bonsai {
     }
 }
 
-fn main() -> Result<(), String> {
-    let event_loop = EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build().map_err(|e| e.to_string())?;
+fn main() -> Result<(), Error> {
+    let event_loop =
+        EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build()?;
     let window = WindowBuilder::new()
         .with_title("Vokselis")
         .with_inner_size(LogicalSize::new(1280, 720))
-        .build(&event_loop)
-        .map_err(|e| e.to_string())?;
+        .build(&event_loop)?;
     let window_size = window.inner_size();
 
     let camera = Camera::new(
@@ -0,0 +1,109 @@
+#![warn(
+absolute_paths_not_starting_with_crate,
+//box_pointers,
+elided_lifetimes_in_paths,
+explicit_outlives_requirements,
+keyword_idents,
+let_underscore_drop,
+macro_use_extern_crate,
+meta_variable_misuse,
+missing_abi,
+//missing_copy_implementations,
+//missing_debug_implementations,
+//missing_docs,
+non_ascii_idents,
+noop_method_call,
+pointer_structural_match,
+rust_2021_incompatible_closure_captures,
+rust_2021_incompatible_or_patterns,
+rust_2021_prefixes_incompatible_syntax,
+rust_2021_prelude_collisions,
+single_use_lifetimes,
+trivial_casts,
+trivial_numeric_casts,
+unreachable_pub,
+//unsafe_code,
+unsafe_op_in_unsafe_fn,
+unstable_features,
+unused_crate_dependencies,
+unused_extern_crates,
+unused_import_braces,
+unused_lifetimes,
+unused_macro_rules,
+unused_qualifications,
+//unused_results,
+unused_tuple_struct_fields,
+variant_size_differences,
+clippy::cargo,
+clippy::complexity,
+clippy::correctness,
+clippy::nursery,
+clippy::pedantic,
+clippy::perf,
+clippy::restriction,
+clippy::style,
+clippy::suspicious,
+)]
+
+use vokselis::{
+    run, Channel, Context, Demo, Error, Shadertoy, ShadertoyBuffer, ShadertoyDesc, ShadertoyPass,
+};
+
+use winit::{
+    dpi::LogicalSize, event_loop::EventLoopBuilder, keyboard::KeyCode, window::WindowBuilder,
+};
+
+use std::path::{Path, PathBuf};
+
+const TOY_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../shaders/toy");
+
+/// Reads the passes from disk so they can be edited while running.
+fn load_toy() -> Result<ShadertoyDesc, Error> {
+    let dir = Path::new(TOY_DIR);
+    let buffer_a = ShadertoyPass::load(dir.join("buffer_a.glsl"))?
+        .with_channel(0, Channel::buffer(ShadertoyBuffer::A))
+        .with_channel(1, Channel::keyboard());
+    let image = ShadertoyPass::load(dir.join("image.glsl"))?
+        .with_channel(0, Channel::buffer(ShadertoyBuffer::A));
+    Ok(ShadertoyDesc::new(image)
+        .with_common(std::fs::read_to_string(dir.join("common.glsl"))?)
+        .with_buffer(ShadertoyBuffer::A, buffer_a))
+}
+
+struct Toy {
+    shadertoy: Shadertoy,
+}
+
+impl Demo for Toy {
+    fn init(ctx: &mut Context) -> Result<Self, Error> {
+        let shadertoy = Shadertoy::new(ctx, load_toy()?)?;
+        Ok(Self { shadertoy })
+    }
+
+    fn update(&mut self, ctx: &mut Context) {
+        let input = ctx.input();
+        if input.is_key_pressed(KeyCode::F5) {
+            // Keep running the old passes if the new ones don't compile
+            if let Err(e) = load_toy().and_then(|desc| self.shadertoy.reload(ctx, desc)) {
+                eprintln!("{e}");
+            }
+        } else if input.is_key_pressed(KeyCode::Backspace) {
+            self.shadertoy.reset();
+        }
+    }
+
+    fn render(&mut self, ctx: &Context) {
+        self.shadertoy.render(ctx);
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let event_loop =
+        EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build()?;
+    let window = WindowBuilder::new()
+        .with_title("Vokselis")
+        .with_inner_size(LogicalSize::new(1280, 720))
+        .build(&event_loop)?;
+
+    run::<Toy>(event_loop, window, None)
+}
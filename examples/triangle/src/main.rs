@@ -45,7 +45,7 @@ clippy::style,
 clippy::suspicious,
 )]
 
-use vokselis::{run, CameraBinding, Context, Demo, Uniform};
+use vokselis::{run, CameraBinding, Context, Demo, Error, Uniform};
 
 use wgpu::StoreOp;
 use winit::{dpi::LogicalSize, event_loop::EventLoopBuilder, window::WindowBuilder};
@@ -92,9 +92,7 @@ impl BasicPipeline {
             multiview: None,
         });
 
-        Self {
-            pipeline,
-        }
+        Self { pipeline }
     }
 }
 
@@ -103,13 +101,16 @@ struct BasicTrig {
 }
 
 impl Demo for BasicTrig {
-    fn init(ctx: &mut Context) -> Self {
+    fn init(ctx: &mut Context) -> Result<Self, Error> {
         let pipeline = BasicPipeline::new(
             &ctx.device,
             ctx.render_backbuffer.format(),
-            wgpu::include_wgsl!("../../../shaders/shader_with_camera.wgsl"),
+            CameraBinding::shader_module_desc(
+                "shader_with_camera.wgsl",
+                include_str!("../../../shaders/shader_with_camera.wgsl"),
+            ),
         );
-        Self { pipeline }
+        Ok(Self { pipeline })
     }
 
     fn render(&mut self, ctx: &Context) {
@@ -144,13 +145,13 @@ impl Demo for BasicTrig {
     }
 }
 
-fn main() -> Result<(), String> {
-    let event_loop = EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build().map_err(|e| e.to_string())?;
+fn main() -> Result<(), Error> {
+    let event_loop =
+        EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build()?;
     let window = WindowBuilder::new()
         .with_title("Vokselis")
         .with_inner_size(LogicalSize::new(1280, 720))
-        .build(&event_loop)
-        .map_err(|e| e.to_string())?;
+        .build(&event_loop)?;
 
     run::<BasicTrig>(event_loop, window, None)
 }
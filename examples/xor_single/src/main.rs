@@ -48,7 +48,10 @@ clippy::suspicious,
 mod raycast;
 mod xor_compute;
 
-use vokselis::{dispatch_optimal, run, Camera, Context, Demo, HdrBackBuffer};
+use vokselis::{
+    dispatch_optimal, run, Camera, CameraBinding, Context, ContextOptions, Demo, DynamicResolution,
+    Error, InteractiveLod,
+};
 
 use bytemuck::{Pod, Zeroable};
 use winit::{dpi::LogicalSize, event_loop::EventLoopBuilder, window::WindowBuilder};
@@ -72,9 +75,23 @@ struct Xor {
 }
 
 impl Demo for Xor {
-    fn init(ctx: &mut Context) -> Self {
+    fn options() -> ContextOptions {
+        ContextOptions::new()
+            .required_features(
+                wgpu::Features::TIMESTAMP_QUERY
+                    | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+            )
+            .dynamic_resolution(DynamicResolution::with_fps(60.))
+            .accumulation(64)
+            .interactive_lod(InteractiveLod::default())
+    }
+
+    fn init(ctx: &mut Context) -> Result<Self, Error> {
         let raycast_single = {
-            let module_desc = wgpu::include_wgsl!("../../../shaders/raycast_compute.wgsl");
+            let module_desc = CameraBinding::shader_module_desc(
+                "raycast_compute.wgsl",
+                include_str!("../../../shaders/raycast_compute.wgsl"),
+            );
             raycast::RaycastPipeline::new(&ctx.device, module_desc.clone(), "single")
         };
 
@@ -112,14 +129,14 @@ impl Demo for Xor {
         drop(cpass);
         ctx.queue.submit(Some(encoder.finish()));
 
-        Self {
+        Ok(Self {
             xor_texture,
             raycast_single,
 
             timestamp,
             timestamp_period,
             timestamp_buffer,
-        }
+        })
     }
 
     fn update(&mut self, ctx: &mut Context) {
@@ -138,10 +155,7 @@ impl Demo for Xor {
                 let nanoseconds =
                     (timestamp_data.end - timestamp_data.start) as f32 * self.timestamp_period;
                 let time_period = std::time::Duration::from_nanos(nanoseconds as _);
-                eprintln!(
-                    "Time on raycast shader: {:?} (single pass)",
-                    time_period
-                );
+                eprintln!("Time on raycast shader: {:?} (single pass)", time_period);
             }
             self.timestamp_buffer.unmap();
         }
@@ -167,12 +181,8 @@ impl Demo for Xor {
         cpass.set_bind_group(1, &ctx.camera_binding.bind_group, &[]);
         cpass.set_bind_group(2, &self.xor_texture.storage_bind_group, &[]);
         cpass.set_bind_group(3, &ctx.render_backbuffer.storage_bind_group, &[]);
-        let (width, height) = HdrBackBuffer::DEFAULT_RESOLUTION;
-        cpass.dispatch_workgroups(
-            dispatch_optimal(width, 8),
-            dispatch_optimal(height, 8),
-            1,
-        );
+        let (width, height) = ctx.render_backbuffer.size();
+        cpass.dispatch_workgroups(dispatch_optimal(width, 8), dispatch_optimal(height, 8), 1);
         drop(cpass);
 
         encoder.write_timestamp(&self.timestamp, 1);
@@ -182,13 +192,13 @@ impl Demo for Xor {
     }
 }
 
-fn main() -> Result<(), String> {
-    let event_loop = EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build().map_err(|e| e.to_string())?;
+fn main() -> Result<(), Error> {
+    let event_loop =
+        EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build()?;
     let window = WindowBuilder::new()
         .with_title("Vokselis")
         .with_inner_size(LogicalSize::new(1280, 720))
-        .build(&event_loop)
-        .map_err(|e| e.to_string())?;
+        .build(&event_loop)?;
     let window_size = window.inner_size();
 
     let camera = Camera::new(
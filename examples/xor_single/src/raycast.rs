@@ -17,9 +17,7 @@ impl RaycastPipeline {
     ) -> Self {
         let module = device.create_shader_module(module_desc);
         let pipeline = Self::make_pipeline(device, module, entry_point);
-        Self {
-            pipeline,
-        }
+        Self { pipeline }
     }
 
     fn make_pipeline(
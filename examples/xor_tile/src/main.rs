@@ -48,22 +48,15 @@ clippy::suspicious,
 mod raycast;
 mod xor_compute;
 
-use vokselis::{dispatch_optimal, run, Camera, Context, Demo, HdrBackBuffer};
+use vokselis::{run, Camera, CameraBinding, Context, ContextOptions, Demo, Error, TiledDispatcher};
 
 use bytemuck::{Pod, Zeroable};
-use wgpu::util::DeviceExt as _;
 use winit::{dpi::LogicalSize, event_loop::EventLoopBuilder, window::WindowBuilder};
 
 use std::path::PathBuf;
 
 const TILE_SIZE: u32 = 256;
-
-#[repr(C)]
-#[derive(Clone, Copy, Pod, Zeroable)]
-pub struct Offset {
-    x: f32,
-    y: f32,
-}
+const TILES_PER_FRAME: u32 = 4;
 
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
@@ -76,9 +69,7 @@ struct Xor {
     xor_texture: xor_compute::XorCompute,
     raycast_tile: raycast::RaycastPipeline,
 
-    offset_buffer_bind_group: wgpu::BindGroup,
-    buffer_len: usize,
-    aligned_offset: u32,
+    tiles: TiledDispatcher,
 
     timestamp: wgpu::QuerySet,
     timestamp_period: f32,
@@ -86,9 +77,24 @@ struct Xor {
 }
 
 impl Demo for Xor {
-    fn init(ctx: &mut Context) -> Self {
+    fn options() -> ContextOptions {
+        ContextOptions::new()
+            .required_features(
+                wgpu::Features::TIMESTAMP_QUERY
+                    | wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+            )
+            .limits(wgpu::Limits {
+                max_bind_groups: 5,
+                ..Default::default()
+            })
+    }
+
+    fn init(ctx: &mut Context) -> Result<Self, Error> {
         let raycast_tile = {
-            let module_desc = wgpu::include_wgsl!("../../../shaders/raycast_compute.wgsl");
+            let module_desc = CameraBinding::shader_module_desc(
+                "raycast_compute.wgsl",
+                include_str!("../../../shaders/raycast_compute.wgsl"),
+            );
             raycast::RaycastPipeline::new(&ctx.device, module_desc, "tile")
         };
 
@@ -97,52 +103,8 @@ impl Demo for Xor {
             xor_compute::XorCompute::new(&ctx.device, shader_module_desc)
         };
 
-        let padding = {
-            let min_align = ctx.limits.min_storage_buffer_offset_alignment;
-            (min_align - std::mem::size_of::<Offset>() as u32 % min_align) % min_align
-        };
-        let offsets = {
-            let mut res = vec![];
-            let (w, h) = HdrBackBuffer::DEFAULT_RESOLUTION;
-            for y in 0..((h / TILE_SIZE) + 1) {
-                for x in 0..((w / TILE_SIZE) + 1) {
-                    res.extend(bytemuck::bytes_of(&Offset {
-                        x: (x * TILE_SIZE) as f32,
-                        y: (y * TILE_SIZE) as f32,
-                    }));
-                    res.extend(std::iter::repeat(0).take(padding as _));
-                }
-            }
-            res
-        };
-        let aligned_offset = std::mem::size_of::<Offset>() as u32 + padding;
-        let buffer_len = offsets.len() / aligned_offset as usize;
-
-        let offset_buffer_bind_group = {
-            let offset_buffer = ctx
-                .device
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Offsets Buffer"),
-                    contents: bytemuck::cast_slice(&offsets),
-                    usage: wgpu::BufferUsages::STORAGE,
-                });
-            let offset_buffer_bind_group_layout = ctx
-                .device
-                .create_bind_group_layout(&raycast::RaycastPipeline::OFFSET_BUFFER_DESC);
-            let offset_buffer_bind_group_desc = wgpu::BindGroupDescriptor {
-                label: Some("Offset Buffer Bind Group"),
-                layout: &offset_buffer_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                        buffer: &offset_buffer,
-                        offset: 0,
-                        size: wgpu::BufferSize::new(std::mem::size_of::<Offset>() as _),
-                    }),
-                }],
-            };
-            ctx.device.create_bind_group(&offset_buffer_bind_group_desc)
-        };
+        let tiles = TiledDispatcher::new(ctx, TILE_SIZE, (16, 16))
+            .with_tiles_per_frame(Some(TILES_PER_FRAME));
 
         let timestamp = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
             label: None,
@@ -173,21 +135,24 @@ impl Demo for Xor {
         drop(cpass);
         ctx.queue.submit(Some(encoder.finish()));
 
-        Self {
+        Ok(Self {
             xor_texture,
             raycast_tile,
 
-            aligned_offset,
-            offset_buffer_bind_group,
-            buffer_len,
+            tiles,
 
             timestamp,
             timestamp_period,
             timestamp_buffer,
-        }
+        })
+    }
+
+    fn resize(&mut self, ctx: &Context) {
+        self.tiles.resize(ctx);
     }
 
     fn update(&mut self, ctx: &mut Context) {
+        self.tiles.update(ctx);
         if ctx.global_uniform.frame % 100 == 0 {
             let _ = self
                 .timestamp_buffer
@@ -203,10 +168,7 @@ impl Demo for Xor {
                 let nanoseconds =
                     (timestamp_data.end - timestamp_data.start) as f32 * self.timestamp_period;
                 let time_period = std::time::Duration::from_nanos(nanoseconds as _);
-                eprintln!(
-                    "Time on raycast shader: {:?} (tile pass)",
-                    time_period
-                );
+                eprintln!("Time on raycast shader: {:?} (tile pass)", time_period);
             }
             self.timestamp_buffer.unmap();
         }
@@ -232,18 +194,7 @@ impl Demo for Xor {
         cpass.set_bind_group(1, &ctx.camera_binding.bind_group, &[]);
         cpass.set_bind_group(2, &self.xor_texture.storage_bind_group, &[]);
         cpass.set_bind_group(3, &ctx.render_backbuffer.storage_bind_group, &[]);
-        for offset in 0..self.buffer_len {
-            cpass.set_bind_group(
-                4,
-                &self.offset_buffer_bind_group,
-                &[offset as u32 * self.aligned_offset],
-            );
-            cpass.dispatch_workgroups(
-                dispatch_optimal(TILE_SIZE, 16),
-                dispatch_optimal(TILE_SIZE, 16),
-                1,
-            );
-        }
+        self.tiles.dispatch(&mut cpass, 4);
         drop(cpass);
 
         encoder.write_timestamp(&self.timestamp, 1);
@@ -253,13 +204,13 @@ impl Demo for Xor {
     }
 }
 
-fn main() -> Result<(), String> {
-    let event_loop = EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build().map_err(|e| e.to_string())?;
+fn main() -> Result<(), Error> {
+    let event_loop =
+        EventLoopBuilder::<(PathBuf, wgpu::ShaderModule)>::with_user_event().build()?;
     let window = WindowBuilder::new()
         .with_title("Vokselis")
         .with_inner_size(LogicalSize::new(1280, 720))
-        .build(&event_loop)
-        .map_err(|e| e.to_string())?;
+        .build(&event_loop)?;
     let window_size = window.inner_size();
 
     let camera = Camera::new(
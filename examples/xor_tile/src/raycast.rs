@@ -3,7 +3,7 @@ use crate::xor_compute;
 use vokselis::{
     camera::CameraBinding,
     context::{HdrBackBuffer, Uniform},
-    NonZeroSized,
+    TiledDispatcher,
 };
 
 pub(crate) struct RaycastPipeline {
@@ -11,21 +11,6 @@ pub(crate) struct RaycastPipeline {
 }
 
 impl RaycastPipeline {
-    pub(crate) const OFFSET_BUFFER_DESC: wgpu::BindGroupLayoutDescriptor<'static> =
-        wgpu::BindGroupLayoutDescriptor {
-            label: Some("Offset Buffer Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::COMPUTE,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Storage { read_only: true },
-                    has_dynamic_offset: true,
-                    min_binding_size: Some(crate::Offset::SIZE),
-                },
-                count: None,
-            }],
-        };
-
     pub(crate) fn new(
         device: &wgpu::Device,
         module_desc: wgpu::ShaderModuleDescriptor<'_>,
@@ -33,9 +18,7 @@ impl RaycastPipeline {
     ) -> Self {
         let module = device.create_shader_module(module_desc);
         let pipeline = Self::make_pipeline(device, module, entry_point);
-        Self {
-            pipeline,
-        }
+        Self { pipeline }
     }
 
     fn make_pipeline(
@@ -50,7 +33,7 @@ impl RaycastPipeline {
                 device.create_bind_group_layout(&xor_compute::XorCompute::DESC_COMPUTE);
             let output_texture_bind_group_layout =
                 device.create_bind_group_layout(&HdrBackBuffer::DESC_COMPUTE);
-            let offset_buffer_bind_group = device.create_bind_group_layout(&Self::OFFSET_BUFFER_DESC);
+            let offset_buffer_bind_group = device.create_bind_group_layout(&TiledDispatcher::DESC);
 
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Compute Raycast Pass Layout"),
@@ -0,0 +1,41 @@
+struct Uniform {
+    pos: vec3<f32>,
+    frame: u32,
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
+    mouse_pressed: u32,
+    time: f32,
+    time_delta: f32,
+    sample_index: u32,
+    quality: f32,
+    jitter: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> un: Uniform;
+
+@group(1) @binding(0)
+var current: texture_2d<f32>;
+@group(2) @binding(0)
+var history: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let vertex_idx = i32(in_vertex_index);
+    let uv = vec2<f32>(f32((vertex_idx << 1u) & 2), f32(vertex_idx & 2));
+    return VertexOutput(vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0));
+}
+
+// Running average of every sample since the last reset
+@fragment
+fn fs_main(vin: VertexOutput) -> @location(0) vec4<f32> {
+    let coord = vec2<i32>(vin.position.xy);
+    let sample = textureLoad(current, coord, 0);
+    let accumulated = textureLoad(history, coord, 0);
+    let weight = 1.0 / (f32(un.sample_index) + 1.0);
+    return mix(accumulated, sample, weight);
+}
@@ -0,0 +1,50 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var src_texture: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let vertex_idx = i32(in_vertex_index);
+    let uv = vec2<f32>(f32((vertex_idx << 1u) & 2), f32(vertex_idx & 2));
+    return VertexOutput(vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0), uv);
+}
+
+// Weights of the three source texels from `2 * dst` on, along one axis.
+// Even sizes average two texels, odd ones spread the extra texel over the
+// whole level so every source texel counts as much.
+fn axis_weights(dst: u32, src_size: u32) -> vec3<f32> {
+    if src_size == 1u {
+        return vec3<f32>(1.0, 0.0, 0.0);
+    }
+    if src_size % 2u == 0u {
+        return vec3<f32>(0.5, 0.5, 0.0);
+    }
+    let half_size = f32(src_size / 2u);
+    let x = f32(dst);
+    return vec3<f32>(half_size - x, half_size, x + 1.0) / f32(src_size);
+}
+
+// Box filter over the texels of the level above covered by this one
+@fragment
+fn fs_main(vin: VertexOutput) -> @location(0) vec4<f32> {
+    let dst = vec2<u32>(vin.position.xy);
+    let src_size = textureDimensions(src_texture);
+    var weights_x = axis_weights(dst.x, src_size.x);
+    var weights_y = axis_weights(dst.y, src_size.y);
+
+    var color = vec4<f32>(0.0);
+    for (var j = 0u; j < 3u; j++) {
+        for (var i = 0u; i < 3u; i++) {
+            let weight = weights_x[i] * weights_y[j];
+            if weight > 0.0 {
+                let src = min(dst * 2u + vec2<u32>(i, j), src_size - 1u);
+                color += weight * textureLoad(src_texture, src, 0);
+            }
+        }
+    }
+    return color;
+}
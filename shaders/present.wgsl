@@ -10,6 +10,9 @@ struct Uniform {
     mouse_pressed: u32,
     time: f32,
     time_delta: f32,
+    sample_index: u32,
+    quality: f32,
+    jitter: vec2<f32>,
 };
 
 @group(0) @binding(0)
@@ -20,6 +23,16 @@ var src_texture: texture_2d<f32>;
 @group(2) @binding(0)
 var src_sampler: sampler;
 
+struct PresentSettings {
+    paper_white: f32,
+    max_luminance: f32,
+    // 0 - bilinear, 1 - bicubic
+    upscale_filter: u32,
+};
+
+@group(3) @binding(0)
+var<uniform> settings: PresentSettings;
+
 fn linear_to_srgb(col: vec4<f32>) -> vec4<f32> {
     let color_linear = col.rgb;
     let selector = ceil(color_linear - 0.0031308);
@@ -89,6 +102,13 @@ fn texture_bicubic(tex: texture_2d<f32>, p: vec2<f32>) -> vec4<f32> {
     return g0(fuv.y) * (g0x * tex_sample(tex, p0) + g1x * tex_sample(tex, p1)) + g1(fuv.y) * (g0x * tex_sample(tex, p2) + g1x * tex_sample(tex, p3));
 }
 
+// Upscales the backbuffer to the surface with the selected filter
+fn sample_backbuffer(uv: vec2<f32>) -> vec4<f32> {
+    if settings.upscale_filter == 1u {
+        return texture_bicubic(src_texture, uv);
+    }
+    return tex_sample(src_texture, uv);
+}
 
 struct VertexOutput {
     @builtin(position) position: vec4<f32>,
@@ -110,14 +130,41 @@ struct FragmentOutput {
 
 @fragment
 fn fs_main(vin: VertexOutput) -> FragmentOutput {
-    let col_tex = tex_sample(src_texture, vin.uv);
+    let col_tex = sample_backbuffer(vin.uv);
     // let col_tex = texture_quadratic(src_texture, vin.uv);
-    // let col_tex = texture_bicubic(src_texture, vin.uv);
     let col_vec = vec4(ACESFilm(col_tex.rgb), col_tex.a);
     let col = linear_to_srgb(col_vec);
     return FragmentOutput(col, col);
 }
 
+// Used when the surface format is sRGB and the hardware does the encoding.
+@fragment
+fn fs_main_srgb(vin: VertexOutput) -> FragmentOutput {
+    let col_tex = sample_backbuffer(vin.uv);
+    let col_vec = vec4(ACESFilm(col_tex.rgb), col_tex.a);
+    return FragmentOutput(col_vec, linear_to_srgb(col_vec));
+}
+
+// scRGB encodes 80 nits as 1.0
+const SCRGB_WHITE_NITS: f32 = 80.0;
+
+// Compresses highlights above the paper white towards the display peak
+fn hdr_rolloff(x: vec3<f32>, peak: f32) -> vec3<f32> {
+    let knee = min(1.0, peak);
+    let over = max(x - knee, vec3(0.0));
+    let range = max(peak - knee, 1e-4);
+    return min(x, vec3(knee)) + range * over / (over + range);
+}
+
+@fragment
+fn fs_main_hdr(vin: VertexOutput) -> FragmentOutput {
+    let col_tex = sample_backbuffer(vin.uv);
+    let peak = settings.max_luminance / settings.paper_white;
+    let hdr = hdr_rolloff(max(col_tex.rgb, vec3(0.0)), peak) * settings.paper_white / SCRGB_WHITE_NITS;
+    let sdr = linear_to_srgb(vec4(ACESFilm(col_tex.rgb), col_tex.a));
+    return FragmentOutput(vec4(hdr, col_tex.a), sdr);
+}
+
 @fragment
 fn fs_main_raw(vin: VertexOutput) -> @location(0) vec4<f32> {
     return textureSample(src_texture, src_sampler, vin.uv);
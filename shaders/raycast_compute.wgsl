@@ -6,12 +6,9 @@ struct Uniform {
     mouse_pressed: u32,
     time: f32,
     time_delta: f32,
-};
-
-struct Camera {
-	view_pos: vec4<f32>,
-	proj_view: mat4x4<f32>,
-	inv_proj: mat4x4<f32>,
+    sample_index: u32,
+    quality: f32,
+    jitter: vec2<f32>,
 };
 
 struct Offset {
@@ -22,6 +19,7 @@ struct Offset {
 @group(0) @binding(0)
 var<uniform> un: Uniform;
 @group(1) @binding(0)
+// `Camera` is declared in src/camera.wgsl, spliced in front of this file.
 var<uniform> cam: Camera;
 @group(2) @binding(0)
 var volume: texture_storage_3d<rgba16float, read_write>;
@@ -52,6 +50,15 @@ fn intersect_box(orig: vec3<f32>, dir: vec3<f32>) -> vec2<f32> {
     return vec2<f32>(t0, t1);
 }
 
+// Interleaved gradient noise, shifted every frame so the pattern doesn't
+// repeat in the history of temporal filters. It stands in for blue noise:
+// it spreads values evenly over 3x3 neighbourhoods, which is what the TAA
+// neighbourhood clamp needs, without a noise texture bound to every pass.
+fn gradient_noise(pixel: vec2<f32>, index: u32) -> f32 {
+    let p = pixel + 5.588238 * f32(index % 64u);
+    return fract(52.9829189 * fract(dot(p, vec2(0.06711056, 0.00583715))));
+}
+
 fn get_cam(eye: vec3<f32>, tar: vec3<f32>) -> mat3x3<f32> {
     let zaxis = normalize(tar - eye);
     let xaxis = normalize(cross(zaxis, vec3(0., 1., 0.)));
@@ -59,14 +66,15 @@ fn get_cam(eye: vec3<f32>, tar: vec3<f32>) -> mat3x3<f32> {
     return mat3x3(xaxis, yaxis, zaxis);
 }
 
-fn get_col2(eye: vec3<f32>, dir: vec3<f32>, tmin: f32, tmax: f32, clear_color: vec4<f32>) -> vec4<f32> {
+fn get_col2(eye: vec3<f32>, dir: vec3<f32>, tmin: f32, tmax: f32, clear_color: vec4<f32>, jitter: f32) -> vec4<f32> {
     var color = vec4(clear_color.rgb, 0.1);
     let light = vec3(0., -1., 0.);
     let block_size = vec3<f32>(textureDimensions(volume));
     let dt_vec = 1.0 / (block_size * abs(dir));
-    let dt_scale = 1.0;
+    // Coarser steps at lower quality
+    let dt_scale = 1.0 / max(un.quality, 0.05);
     let dt = dt_scale * max(min(dt_vec.x, min(dt_vec.y, dt_vec.z)), 0.01);
-    for (var t = tmin; t < tmax; t = t + dt) {
+    for (var t = tmin + jitter * dt; t < tmax; t = t + dt) {
         var p = eye + t * dir;
         let samp = vec3<i32>((p + 1.) * (block_size / 2.));
         let vol_content = textureLoad(volume, samp);
@@ -77,6 +85,8 @@ fn get_col2(eye: vec3<f32>, dir: vec3<f32>, tmin: f32, tmax: f32, clear_color: v
 
         var vol_alpha = pow(vol_content.a, 3.0);
         vol_alpha = smoothstep(0.0, 0.7, vol_alpha);
+        // Opacity correction
+        vol_alpha = 1.0 - pow(1.0 - vol_alpha, dt_scale);
 
         var directional = 3.0 * vec3(1., .1, .13) * max(dot(normal.xyz, normalize(vec3(-2., -2., -1.))), .0);
         directional *= smoothstep(.3, 1.5, dot(p, normalize(vec3(1., 1., -1.))));
@@ -99,18 +109,27 @@ fn get_col2(eye: vec3<f32>, dir: vec3<f32>, tmin: f32, tmax: f32, clear_color: v
 fn render(global_id: vec2<u32>, offset_x: f32, offset_y: f32) -> vec4<f32> {
     let time = un.time * 0.5;
 
-    let coord = vec2<f32>(global_id) + vec2(offset_x, offset_y);
+    let coord = vec2<f32>(global_id) + vec2(offset_x, offset_y) + un.jitter;
+
+    // Ray start jitter trades banding for noise the temporal filters remove
+    var jitter = 0.;
+    if (un.sample_index > 0u || any(un.jitter != vec2(0.))) {
+        jitter = gradient_noise(coord, un.frame + un.sample_index);
+    }
     let dims = vec2<f32>(textureDimensions(out_tex));
     let aspect_ratio = dims.y / dims.x;
 
     var screen_coord = 2. * vec2(coord.x, coord.y) / dims - 1.;
     screen_coord.y *= -aspect_ratio;
 
-    let screen_point = vec4(screen_coord, 0., 1.);
-    let screen_tangent = screen_point + vec4(0., 0., 1., 0.);
+    // Near plane and a finite point behind it, reverse-Z puts infinity at 0
+    let near_depth = select(0., 1., cam.reverse_z == 1u);
+    let far_depth = select(1., 0.5, cam.reverse_z == 1u);
+    let screen_point = vec4(screen_coord, near_depth, 1.);
+    let screen_tangent = vec4(screen_coord, far_depth, 1.);
 
-    var view_pos = cam.inv_proj * screen_point;
-    var view_tang = cam.inv_proj * screen_tangent;
+    var view_pos = cam.inv_proj_view * screen_point;
+    var view_tang = cam.inv_proj_view * screen_tangent;
 
     let eye = view_pos.xyz / view_pos.w;
     let dir = normalize(view_tang.xyz / view_tang.w - eye);
@@ -122,7 +141,7 @@ fn render(global_id: vec2<u32>, offset_x: f32, offset_y: f32) -> vec4<f32> {
         var t_hit = intersect_box(eye, dir);
         if (t_hit.x < t_hit.y) {
             t_hit.x = max(t_hit.x, 0.0);
-            color = vec4(get_col2(eye, dir, t_hit.x, t_hit.y, clear_color).rgb, 1.);
+            color = vec4(get_col2(eye, dir, t_hit.x, t_hit.y, clear_color, jitter).rgb, 1.);
         } else {
             color = vec4(clear_color.rgb, 1.);
         }
@@ -20,17 +20,15 @@ struct Uniform {
     mouse_pressed: u32,
     time: f32,
     time_delta: f32,
-};
-
-struct Camera {
-	view_pos: vec4<f32>,
-	proj_view: mat4x4<f32>,
-	inv_proj: mat4x4<f32>,
+    sample_index: u32,
+    quality: f32,
+    jitter: vec2<f32>,
 };
 
 @group(0) @binding(0)
 var<uniform> un: Uniform;
 @group(1) @binding(0)
+// `Camera` is declared in src/camera.wgsl, spliced in front of this file.
 var<uniform> cam: Camera;
 @group(2) @binding(0)
 var volume: texture_3d<f32>;
@@ -42,8 +40,18 @@ fn vs_main(vert: VertexInput) -> VertexOutput {
     var vout: VertexOutput;
     var pos = vert.position;
     vout.position = cam.proj_view * vec4<f32>(pos, 1.0);
-    vout.transformed_eye = cam.view_pos.xyz;
-    vout.ray_dir = pos - vout.transformed_eye;
+    // Sub-pixel jitter in clip space
+    vout.position.x += 2.0 * un.jitter.x / un.resolution.x * vout.position.w;
+    vout.position.y -= 2.0 * un.jitter.y / un.resolution.y * vout.position.w;
+    if (cam.orthographic == 1u) {
+        // Parallel rays starting on the plane through the camera
+        let forward = -normalize(cam.inv_view[2].xyz);
+        vout.transformed_eye = pos - forward * dot(pos - cam.view_pos.xyz, forward);
+        vout.ray_dir = forward;
+    } else {
+        vout.transformed_eye = cam.view_pos.xyz;
+        vout.ray_dir = pos - vout.transformed_eye;
+    }
     return vout;
 }
 
@@ -60,6 +68,15 @@ fn intersect_box(orig: float3, dir: float3) -> float2 {
     return float2(t0, t1);
 }
 
+// Interleaved gradient noise, shifted every frame so the pattern doesn't
+// repeat in the history of temporal filters. It stands in for blue noise:
+// it spreads values evenly over 3x3 neighbourhoods, which is what the TAA
+// neighbourhood clamp needs, without a noise texture bound to every pass.
+fn gradient_noise(pixel: vec2<f32>, index: u32) -> f32 {
+    let p = pixel + 5.588238 * f32(index % 64u);
+    return fract(52.9829189 * fract(dot(p, vec2(0.06711056, 0.00583715))));
+}
+
 fn linear_to_srgb(x: f32) -> f32 {
     if (x <= 0.0031308) {
         return 12.92 * x;
@@ -95,8 +112,13 @@ fn fs_main(vin: VertexOutput) -> @location(0) float4 {
 
     var color = vec4<f32>(0.0);
     let dt_vec = 1.0 / (vec3<f32>(256.0) * abs(ray_dir));
-    let dt_scale = 1.0;
+    // Coarser steps at lower quality
+    let dt_scale = 1.0 / max(un.quality, 0.05);
     let dt = dt_scale * min(dt_vec.x, min(dt_vec.y, dt_vec.z));
+    // Ray start jitter trades banding for noise the temporal filters remove
+    if (un.sample_index > 0u || any(un.jitter != vec2(0.))) {
+        t_hit.x += dt * gradient_noise(vin.position.xy, un.frame + un.sample_index);
+    }
     var p = eye + t_hit.x * ray_dir;
     for (var t = t_hit.x; t < t_hit.y; t = t + dt) {
         let tex_content = textureSampleLevel(volume, tex_sampler, p, 0.0);
@@ -108,7 +130,7 @@ fn fs_main(vin: VertexOutput) -> @location(0) float4 {
         var val_color = vec4<f32>(vertigo(val.r), val.r);
 
 		// Opacity correction
-        // val_color.a = 1.0 - pow(1.0 - val_color.a, dt_scale);
+        val_color.a = 1.0 - pow(1.0 - val_color.a, dt_scale);
         var tmp = color.rgb + (1.0 - color.a) * val_color.a * val_color.xyz + background.rgb * background.a * (1. - val_alpha);
         color = vec4<f32>(tmp, color.a);
         color.a = color.a + (1.0 - color.a) * val_color.a;
@@ -6,17 +6,15 @@ struct Uniform {
     mouse_pressed: u32,
     time: f32,
     time_delta: f32,
-};
-
-struct Camera {
-	view_pos: vec3<f32>,
-	proj_view: mat4x4<f32>,
-	inv_proj: mat4x4<f32>,
+    sample_index: u32,
+    quality: f32,
+    jitter: vec2<f32>,
 };
 
 @group(0) @binding(0)
 var<uniform> un: Uniform;
 @group(1) @binding(0)
+// `Camera` is declared in src/camera.wgsl, spliced in front of this file.
 var<uniform> cam: Camera;
 
 struct VertexOutput {
@@ -0,0 +1,21 @@
+
+layout(location = 0) out vec4 vokselis_frag_color;
+
+vec3 vokselis_srgb_to_linear(vec3 color) {
+    color = max(color, vec3(0.0));
+    return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)), step(0.04045, color));
+}
+
+void main() {
+    vec4 color = vec4(0.0, 0.0, 0.0, 1.0);
+#ifdef VOKSELIS_IMAGE_PASS
+    // Buffers are stored bottom row first like in Shadertoy, only the
+    // image is flipped for presentation
+    mainImage(color, vec2(gl_FragCoord.x, iResolution.y - gl_FragCoord.y));
+    // Shadertoy writes display values, the backbuffer is linear
+    vokselis_frag_color = vec4(vokselis_srgb_to_linear(color.rgb), 1.0);
+#else
+    mainImage(color, gl_FragCoord.xy);
+    vokselis_frag_color = color;
+#endif
+}
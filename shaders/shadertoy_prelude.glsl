@@ -0,0 +1,31 @@
+#version 450
+
+// Shadertoy inputs, the layout matches `ShadertoyUniform`
+layout(set = 0, binding = 0) uniform ShadertoyInputs {
+    vec3 iResolution;
+    float iTime;
+    vec4 iMouse;
+    vec4 iDate;
+    vec3 iChannelResolution[4];
+    float iChannelTime[4];
+    float iTimeDelta;
+    float iFrameRate;
+    int iFrame;
+    float iSampleRate;
+};
+
+layout(set = 1, binding = 0) uniform texture2D vokselis_channel0;
+layout(set = 1, binding = 1) uniform sampler vokselis_sampler0;
+layout(set = 1, binding = 2) uniform texture2D vokselis_channel1;
+layout(set = 1, binding = 3) uniform sampler vokselis_sampler1;
+layout(set = 1, binding = 4) uniform texture2D vokselis_channel2;
+layout(set = 1, binding = 5) uniform sampler vokselis_sampler2;
+layout(set = 1, binding = 6) uniform texture2D vokselis_channel3;
+layout(set = 1, binding = 7) uniform sampler vokselis_sampler3;
+
+// Combined samplers can't be passed to functions, use the channels directly
+#define iChannel0 sampler2D(vokselis_channel0, vokselis_sampler0)
+#define iChannel1 sampler2D(vokselis_channel1, vokselis_sampler1)
+#define iChannel2 sampler2D(vokselis_channel2, vokselis_sampler2)
+#define iChannel3 sampler2D(vokselis_channel3, vokselis_sampler3)
+
@@ -0,0 +1,81 @@
+struct Uniform {
+    pos: vec3<f32>,
+    frame: u32,
+    resolution: vec2<f32>,
+    mouse: vec2<f32>,
+    mouse_pressed: u32,
+    time: f32,
+    time_delta: f32,
+    sample_index: u32,
+    quality: f32,
+    jitter: vec2<f32>,
+};
+
+struct TaaSettings {
+    focus_depth: f32,
+    blend: f32,
+    history_valid: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> un: Uniform;
+@group(1) @binding(0)
+// `Camera` is declared in src/camera.wgsl, spliced in front of this file.
+var<uniform> cam: Camera;
+@group(2) @binding(0)
+var current: texture_2d<f32>;
+@group(3) @binding(0)
+var history: texture_2d<f32>;
+@group(3) @binding(1)
+var history_sampler: sampler;
+@group(3) @binding(2)
+var<uniform> settings: TaaSettings;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
+    let vertex_idx = i32(in_vertex_index);
+    let uv = vec2<f32>(f32((vertex_idx << 1u) & 2), f32(vertex_idx & 2));
+    return VertexOutput(vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0));
+}
+
+// Screen position of the pixel in the previous frame. Without depth the
+// surface is assumed to lie at the distance the camera focuses on.
+fn reproject(uv: vec2<f32>) -> vec2<f32> {
+    let ndc = vec2(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    let world = cam.inv_proj_view * vec4(ndc, settings.focus_depth, 1.0);
+    let prev = cam.prev_proj_view * vec4(world.xyz / world.w, 1.0);
+    let prev_ndc = prev.xy / prev.w;
+    return vec2(prev_ndc.x * 0.5 + 0.5, 0.5 - prev_ndc.y * 0.5);
+}
+
+@fragment
+fn fs_main(vin: VertexOutput) -> @location(0) vec4<f32> {
+    let coord = vec2<i32>(vin.position.xy);
+    let dims = vec2<i32>(textureDimensions(current));
+    let color = textureLoad(current, coord, 0);
+    if (settings.history_valid == 0u) {
+        return color;
+    }
+
+    let prev_uv = reproject(vin.position.xy / vec2<f32>(dims));
+    if (any(prev_uv < vec2(0.)) || any(prev_uv > vec2(1.))) {
+        return color;
+    }
+
+    // Neighbourhood clamping rejects history that doesn't fit the current frame
+    var color_min = color;
+    var color_max = color;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbour = textureLoad(current, clamp(coord + vec2(x, y), vec2(0), dims - 1), 0);
+            color_min = min(color_min, neighbour);
+            color_max = max(color_max, neighbour);
+        }
+    }
+    let history_color = textureSampleLevel(history, history_sampler, prev_uv, 0.0);
+    return mix(clamp(history_color, color_min, color_max), color, settings.blend);
+}
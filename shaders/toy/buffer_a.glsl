@@ -0,0 +1,23 @@
+// Paint with the left mouse button, a blob orbits the center on its own.
+// Trails fade out over time, space wipes the canvas.
+// iChannel0: Buffer A, iChannel1: Keyboard
+
+const int KEY_SPACE = 32;
+
+float brush(vec2 fragCoord, vec2 center, float radius) {
+    return smoothstep(radius, 0.0, distance(fragCoord, center));
+}
+
+void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+    vec2 uv = fragCoord / iResolution.xy;
+    float trail = texture(iChannel0, uv).x * exp(-0.8 * iTimeDelta);
+
+    vec2 orbit = iResolution.xy * (0.5 + 0.3 * vec2(cos(iTime), sin(1.3 * iTime)));
+    trail = max(trail, brush(fragCoord, orbit, 0.03 * iResolution.y));
+    if (iMouse.z > 0.0) {
+        trail = max(trail, brush(fragCoord, iMouse.xy, 0.05 * iResolution.y));
+    }
+
+    float wipe = texelFetch(iChannel1, ivec2(KEY_SPACE, 0), 0).x;
+    fragColor = vec4(trail * (1.0 - wipe), 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,9 @@
+// Colors the trails of Buffer A.
+// iChannel0: Buffer A
+
+void mainImage(out vec4 fragColor, in vec2 fragCoord) {
+    vec2 uv = fragCoord / iResolution.xy;
+    float trail = texture(iChannel0, uv).x;
+    vec3 color = palette(trail + 0.1 * iTime) * trail;
+    fragColor = vec4(color, 1.0);
+}
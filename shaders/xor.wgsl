@@ -6,6 +6,9 @@ struct Uniform {
     mouse_pressed: u32,
     time: f32,
     time_delta: f32,
+    sample_index: u32,
+    quality: f32,
+    jitter: vec2<f32>,
 };
 
 @group(0) @binding(0)
@@ -1,22 +1,71 @@
+mod animation;
+mod animation_controller;
+mod arcball_controller;
+mod bookmarks;
+mod controller;
+mod fly_controller;
+mod orbit_controller;
+
+pub use animation::{CameraAnimation, CameraKeyframe, Interpolation};
+pub use animation_controller::{AnimationController, Playback};
+pub use arcball_controller::ArcballController;
+pub use bookmarks::{Bookmark, CameraBookmarks, CameraView};
+pub use controller::CameraController;
+pub use fly_controller::FlyController;
+pub use orbit_controller::OrbitController;
+
+pub(crate) use bookmarks::CameraTransition;
+
 use crate::NonZeroSized;
 
 use glam::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
 use wgpu::util::DeviceExt;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct CameraUniform {
     pub view_position: [f32; 4],
+    pub view: [[f32; 4]; 4],
+    pub proj: [[f32; 4]; 4],
     pub proj_view: [[f32; 4]; 4],
+    pub inv_view: [[f32; 4]; 4],
     pub inv_proj: [[f32; 4]; 4],
+    pub inv_proj_view: [[f32; 4]; 4],
+    /// `proj_view` of the previous frame, used to reproject history.
+    pub prev_proj_view: [[f32; 4]; 4],
+    pub znear: f32,
+    pub zfar: f32,
+    /// Vertical field of view in radians.
+    pub fovy: f32,
+    pub aspect: f32,
+    /// Size in pixels of the image the camera renders to.
+    pub viewport: [f32; 2],
+    /// `1` for [`Projection::Orthographic`].
+    pub orthographic: u32,
+    /// `1` when depth goes from 1 at the near plane to 0 at infinity.
+    pub reverse_z: u32,
 }
 
 impl Default for CameraUniform {
     fn default() -> Self {
+        let identity = Mat4::IDENTITY.to_cols_array_2d();
         Self {
             view_position: [0.0; 4],
-            proj_view: Mat4::IDENTITY.to_cols_array_2d(),
-            inv_proj: Mat4::IDENTITY.to_cols_array_2d(),
+            view: identity,
+            proj: identity,
+            proj_view: identity,
+            inv_view: identity,
+            inv_proj: identity,
+            inv_proj_view: identity,
+            prev_proj_view: identity,
+            znear: Camera::ZNEAR,
+            zfar: Camera::ZFAR,
+            fovy: Camera::FOVY,
+            aspect: 1.,
+            viewport: [1.; 2],
+            orthographic: 0,
+            reverse_z: 0,
         }
     }
 }
@@ -24,6 +73,9 @@ impl Default for CameraUniform {
 pub struct CameraBinding {
     pub buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
+    uniform: CameraUniform,
+    /// `proj_view` of the last presented frame.
+    rendered_proj_view: [[f32; 4]; 4],
 }
 
 impl CameraBinding {
@@ -41,6 +93,21 @@ impl CameraBinding {
         }],
     };
 
+    /// WGSL declaration of [`CameraUniform`] as `struct Camera`.
+    pub const WGSL: &str = include_str!("camera.wgsl");
+
+    /// Shader module of `source` with [`CameraBinding::WGSL`] spliced in
+    /// front of it.
+    pub fn shader_module_desc<'a>(
+        label: &'a str,
+        source: &str,
+    ) -> wgpu::ShaderModuleDescriptor<'a> {
+        wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(format!("{}{source}", Self::WGSL).into()),
+        }
+    }
+
     pub fn new(device: &wgpu::Device) -> Self {
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
@@ -57,19 +124,77 @@ impl CameraBinding {
             }],
         });
 
-        Self { buffer, bind_group }
+        let uniform = CameraUniform::default();
+        Self {
+            buffer,
+            bind_group,
+            uniform,
+            rendered_proj_view: uniform.proj_view,
+        }
     }
 
+    /// Uploads the camera when it changed, or when the previous frame matrix
+    /// changed with [`CameraBinding::frame_rendered`].
     pub fn update(&mut self, queue: &wgpu::Queue, camera: &mut Camera) {
-        if camera.updated {
-            queue.write_buffer(
-                &self.buffer,
-                0,
-                bytemuck::bytes_of(&camera.get_proj_view_matrix()),
-            );
+        if camera.updated || self.uniform.prev_proj_view != self.rendered_proj_view {
+            let mut uniform = camera.get_proj_view_matrix();
+            uniform.prev_proj_view = self.rendered_proj_view;
+            queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+            self.uniform = uniform;
             camera.updated = false;
         }
     }
+
+    /// Makes the uploaded matrix the previous frame one, call it once per
+    /// presented frame. Updates between two frames keep reprojecting
+    /// against the frame that was actually rendered.
+    pub fn frame_rendered(&mut self) {
+        self.rendered_proj_view = self.uniform.proj_view;
+    }
+}
+
+/// Built-in [`CameraController`]s, [`Input::NEXT_CAMERA_MODE`](crate::Input::NEXT_CAMERA_MODE)
+/// (`Tab` by default) cycles through them in [`run`](crate::run).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CameraMode {
+    /// See [`OrbitController`].
+    #[default]
+    Orbit,
+    /// See [`FlyController`].
+    Fly,
+    /// See [`ArcballController`].
+    Arcball,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Orbit => Self::Fly,
+            Self::Fly => Self::Arcball,
+            Self::Arcball => Self::Orbit,
+        }
+    }
+
+    /// Controller with default settings.
+    pub fn controller(self) -> Box<dyn CameraController> {
+        match self {
+            Self::Orbit => Box::<OrbitController>::default(),
+            Self::Fly => Box::<FlyController>::default(),
+            Self::Arcball => Box::<ArcballController>::default(),
+        }
+    }
+}
+
+/// How [`Camera`] maps view space to clip space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Projection {
+    #[default]
+    Perspective,
+    /// Parallel rays, the view height matches the perspective one at the
+    /// target distance so switching keeps the framing.
+    Orthographic,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -81,8 +206,17 @@ pub struct Camera {
     pub yaw: f32,
     pub up: Vec3,
     pub aspect: f32,
+    /// Size in pixels of the image rendered with the camera.
+    pub viewport: (u32, u32),
+    /// Vertical field of view in radians.
+    pub fovy: f32,
+    pub znear: f32,
+    pub zfar: f32,
+    pub projection: Projection,
+    /// Infinite far plane with depth reversed, ignores `zfar` in perspective.
+    pub reverse_z: bool,
 
-    updated: bool,
+    pub(crate) updated: bool,
 }
 
 impl Camera {
@@ -100,6 +234,12 @@ impl Camera {
             target,
             up: Self::UP,
             aspect,
+            viewport: (1, 1),
+            fovy: Self::FOVY,
+            znear: Self::ZNEAR,
+            zfar: Self::ZFAR,
+            projection: Projection::Perspective,
+            reverse_z: false,
 
             updated: false,
         };
@@ -107,14 +247,87 @@ impl Camera {
         camera
     }
 
+    pub fn with_fovy(mut self, fovy: f32) -> Self {
+        self.set_fovy(fovy);
+        self
+    }
+
+    pub fn with_clip_planes(mut self, znear: f32, zfar: f32) -> Self {
+        self.set_clip_planes(znear, zfar);
+        self
+    }
+
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.set_projection(projection);
+        self
+    }
+
+    pub fn with_reverse_z(mut self, reverse_z: bool) -> Self {
+        self.set_reverse_z(reverse_z);
+        self
+    }
+
+    pub fn set_fovy(&mut self, fovy: f32) {
+        self.fovy = fovy.clamp(0.01, std::f32::consts::PI - 0.01);
+        self.updated = true;
+    }
+
+    pub fn set_clip_planes(&mut self, znear: f32, zfar: f32) {
+        self.znear = znear.max(f32::EPSILON);
+        self.zfar = zfar.max(self.znear * 2.);
+        self.set_zoom(self.zoom);
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+        self.updated = true;
+    }
+
+    pub fn set_reverse_z(&mut self, reverse_z: bool) {
+        self.reverse_z = reverse_z;
+        self.set_zoom(self.zoom);
+    }
+
+    /// Far plane distance, infinite for reverse-Z perspective.
+    pub fn far_plane(&self) -> f32 {
+        if self.reverse_z && self.projection == Projection::Perspective {
+            f32::INFINITY
+        } else {
+            self.zfar
+        }
+    }
+
+    pub fn build_view_matrix(&self) -> Mat4 {
+        Mat4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    pub fn build_projection_matrix(&self) -> Mat4 {
+        match (self.projection, self.reverse_z) {
+            (Projection::Perspective, false) => {
+                Mat4::perspective_rh(self.fovy, self.aspect, self.znear, self.zfar)
+            }
+            (Projection::Perspective, true) => {
+                Mat4::perspective_infinite_reverse_rh(self.fovy, self.aspect, self.znear)
+            }
+            (Projection::Orthographic, reverse_z) => {
+                let top = self.zoom * (self.fovy / 2.).tan();
+                let right = top * self.aspect;
+                let (near, far) = if reverse_z {
+                    (self.zfar, self.znear)
+                } else {
+                    (self.znear, self.zfar)
+                };
+                Mat4::orthographic_rh(-right, right, -top, top, near, far)
+            }
+        }
+    }
+
     pub fn build_projection_view_matrix(&self) -> Mat4 {
-        let view = Mat4::look_at_rh(self.eye, self.target, self.up);
-        let proj = Mat4::perspective_rh(Self::FOVY, self.aspect, Self::ZNEAR, Self::ZFAR);
-        proj * view
+        self.build_projection_matrix() * self.build_view_matrix()
     }
 
     pub fn set_zoom(&mut self, zoom: f32) {
-        self.zoom = zoom.clamp(0.3, Self::ZFAR / 2.);
+        self.zoom = zoom.clamp(0.3, (self.far_plane() / 2.).max(0.3));
         self.fix_eye();
         self.updated = true;
     }
@@ -124,10 +337,12 @@ impl Camera {
     }
 
     pub fn set_pitch(&mut self, pitch: f32) {
+        let roll = self.roll();
         self.pitch = pitch.clamp(
             -std::f32::consts::PI / 2.0 + f32::EPSILON,
             std::f32::consts::PI / 2.0 - f32::EPSILON,
         );
+        self.set_roll(roll);
         self.fix_eye();
         self.updated = true;
     }
@@ -137,7 +352,9 @@ impl Camera {
     }
 
     pub fn set_yaw(&mut self, yaw: f32) {
+        let roll = self.roll();
         self.yaw = yaw;
+        self.set_roll(roll);
         self.fix_eye();
         self.updated = true;
     }
@@ -146,28 +363,188 @@ impl Camera {
         self.set_yaw(self.yaw + delta);
     }
 
-    fn fix_eye(&mut self) {
+    /// Unit vector from the eye to the target.
+    pub fn forward(&self) -> Vec3 {
         let pitch_cos = self.pitch.cos();
-        self.eye = self.target
-            - self.zoom
-                * Vec3::new(
-                    self.yaw.sin() * pitch_cos,
-                    self.pitch.sin(),
-                    self.yaw.cos() * pitch_cos,
-                );
+        Vec3::new(
+            self.yaw.sin() * pitch_cos,
+            self.pitch.sin(),
+            self.yaw.cos() * pitch_cos,
+        )
+    }
+
+    /// Camera space up vector, perpendicular to [`Camera::forward`].
+    pub fn camera_up(&self) -> Vec3 {
+        self.right().cross(self.forward())
+    }
+
+    pub fn right(&self) -> Vec3 {
+        self.forward().cross(self.up).normalize_or_zero()
+    }
+
+    /// Up vector of the camera without roll, how `forward` changes with the
+    /// pitch. Unlike the world up it's defined looking straight up or down.
+    fn unrolled_up(&self) -> Vec3 {
+        let pitch_sin = self.pitch.sin();
+        Vec3::new(
+            -self.yaw.sin() * pitch_sin,
+            self.pitch.cos(),
+            -self.yaw.cos() * pitch_sin,
+        )
+    }
+
+    /// Cosine and sine of the angle from the unrolled up to `up`, both scaled
+    /// by the length of `up` across the view direction.
+    fn roll(&self) -> (f32, f32) {
+        let unrolled_up = self.unrolled_up();
+        let side = self.forward().cross(unrolled_up);
+        (self.up.dot(unrolled_up), self.up.dot(side))
+    }
+
+    /// Turns `up` by a [`Camera::roll`] taken before yaw or pitch changed.
+    fn set_roll(&mut self, (cos, sin): (f32, f32)) {
+        if sin.abs() <= 1e-6 && cos > 0. {
+            self.up = Self::UP;
+            return;
+        }
+        let unrolled_up = self.unrolled_up();
+        let side = self.forward().cross(unrolled_up);
+        self.up = (unrolled_up * cos + side * sin).normalize();
+    }
+
+    /// Points the camera along `forward` keeping the target, `up` may be
+    /// anything not parallel to `forward` so the view can go upside down.
+    ///
+    /// [`Camera::set_yaw`] and [`Camera::set_pitch`] keep the roll.
+    pub fn set_orientation(&mut self, forward: Vec3, up: Vec3) {
+        let forward = forward.normalize();
+        let up = (up - forward * up.dot(forward)).normalize_or_zero();
+        if up == Vec3::ZERO {
+            return;
+        }
+        self.pitch = forward.y.clamp(-1., 1.).asin();
+        self.yaw = forward.x.atan2(forward.z);
+        self.up = up;
+        self.fix_eye();
+        self.updated = true;
+    }
+
+    /// Places the eye at `eye` looking at `target`, unlike [`Camera::set_zoom`]
+    /// the distance between them isn't clamped.
+    pub fn look_at(&mut self, eye: Vec3, target: Vec3, up: Vec3) {
+        self.target = target;
+        self.zoom = eye.distance(target).max(f32::EPSILON);
+        self.set_orientation(target - eye, up);
+        self.fix_eye();
+        self.updated = true;
+    }
+
+    /// Moves the eye and the target by `delta`.
+    pub fn translate(&mut self, delta: Vec3) {
+        self.target += delta;
+        self.fix_eye();
+        self.updated = true;
+    }
+
+    /// Turns the view around the eye, unlike [`Camera::add_yaw`] and
+    /// [`Camera::add_pitch`] which orbit the eye around the target.
+    pub fn look(&mut self, yaw_delta: f32, pitch_delta: f32) {
+        let eye = self.eye;
+        self.set_yaw(self.yaw + yaw_delta);
+        self.set_pitch(self.pitch + pitch_delta);
+        self.target = eye + self.zoom * self.forward();
+        self.fix_eye();
+    }
+
+    fn fix_eye(&mut self) {
+        self.eye = self.target - self.zoom * self.forward();
     }
 
+    /// Sets the viewport size in pixels and the aspect ratio following it.
     pub fn set_aspect(&mut self, width: u32, height: u32) {
         self.aspect = width as f32 / height as f32;
+        self.viewport = (width, height);
         self.updated = true;
     }
 
     pub fn get_proj_view_matrix(&self) -> CameraUniform {
-        let proj_view = self.build_projection_view_matrix();
+        let view = self.build_view_matrix();
+        let proj = self.build_projection_matrix();
+        let proj_view = proj * view;
         CameraUniform {
             view_position: [self.eye.x, self.eye.y, self.eye.z, 1.0],
+            view: view.to_cols_array_2d(),
+            proj: proj.to_cols_array_2d(),
             proj_view: proj_view.to_cols_array_2d(),
-            inv_proj: proj_view.inverse().to_cols_array_2d(),
+            inv_view: view.inverse().to_cols_array_2d(),
+            inv_proj: proj.inverse().to_cols_array_2d(),
+            inv_proj_view: proj_view.inverse().to_cols_array_2d(),
+            prev_proj_view: proj_view.to_cols_array_2d(),
+            znear: self.znear,
+            zfar: self.far_plane(),
+            fovy: self.fovy,
+            aspect: self.aspect,
+            viewport: [self.viewport.0 as f32, self.viewport.1 as f32],
+            orthographic: (self.projection == Projection::Orthographic) as _,
+            reverse_z: self.reverse_z as _,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glam::Quat;
+
+    #[test]
+    fn yaw_and_pitch_keep_roll() {
+        let mut camera = Camera::new(2., 0.3, 0.5, Vec3::ZERO, 1.);
+        let rolled = Quat::from_axis_angle(camera.forward(), 0.4) * camera.camera_up();
+        camera.set_orientation(camera.forward(), rolled);
+        assert!((camera.camera_up().angle_between(camera.unrolled_up()) - 0.4).abs() < 1e-4);
+
+        camera.add_yaw(0.7);
+        camera.add_pitch(-0.5);
+        assert!((camera.camera_up().angle_between(camera.unrolled_up()) - 0.4).abs() < 1e-4);
+        assert!(camera.up.dot(camera.forward()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn yaw_and_pitch_keep_world_up_without_roll() {
+        let mut camera = Camera::new(2., 0.3, 0.5, Vec3::ZERO, 1.);
+        camera.add_yaw(0.7);
+        camera.add_pitch(-0.5);
+        assert_eq!(camera.up, Vec3::Y);
+    }
+
+    #[test]
+    fn camera_shaders_validate() {
+        use wgpu::naga;
+
+        for (name, source) in [
+            (
+                "raycast_compute",
+                include_str!("../shaders/raycast_compute.wgsl"),
+            ),
+            (
+                "raycast_naive",
+                include_str!("../shaders/raycast_naive.wgsl"),
+            ),
+            (
+                "shader_with_camera",
+                include_str!("../shaders/shader_with_camera.wgsl"),
+            ),
+            ("taa", include_str!("../shaders/taa.wgsl")),
+        ] {
+            let module = naga::front::wgsl::parse_str(&format!("{}{source}", CameraBinding::WGSL))
+                .unwrap_or_else(|e| panic!("{name}.wgsl should parse: {e}"));
+            naga::valid::Validator::new(
+                naga::valid::ValidationFlags::all(),
+                naga::valid::Capabilities::all(),
+            )
+            .validate(&module)
+            .unwrap_or_else(|e| panic!("{name}.wgsl should validate: {e:?}"));
         }
     }
 }
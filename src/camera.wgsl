@@ -0,0 +1,18 @@
+// Layout of `CameraUniform` in src/camera.rs, keep the two in sync.
+struct Camera {
+    view_pos: vec4<f32>,
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+    proj_view: mat4x4<f32>,
+    inv_view: mat4x4<f32>,
+    inv_proj: mat4x4<f32>,
+    inv_proj_view: mat4x4<f32>,
+    prev_proj_view: mat4x4<f32>,
+    znear: f32,
+    zfar: f32,
+    fovy: f32,
+    aspect: f32,
+    viewport: vec2<f32>,
+    orthographic: u32,
+    reverse_z: u32,
+};
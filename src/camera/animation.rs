@@ -0,0 +1,417 @@
+use crate::Camera;
+
+use glam::{Mat3, Quat, Vec3};
+
+use std::ops::{Add, Mul, Sub};
+
+/// Camera pose at a point of a [`CameraAnimation`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraKeyframe {
+    /// Seconds from the start of the animation.
+    pub time: f32,
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    /// Vertical field of view in radians.
+    pub fovy: f32,
+}
+
+impl CameraKeyframe {
+    pub fn new(time: f32, eye: Vec3, target: Vec3, up: Vec3, fovy: f32) -> Self {
+        Self {
+            time,
+            eye,
+            target,
+            up,
+            fovy,
+        }
+    }
+
+    pub fn from_camera(time: f32, camera: &Camera) -> Self {
+        Self::new(
+            time,
+            camera.eye,
+            camera.target,
+            camera.camera_up(),
+            camera.fovy,
+        )
+    }
+
+    /// Moves `camera` to the keyframe pose.
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.look_at(self.eye, self.target, self.up);
+        camera.set_fovy(self.fovy);
+    }
+
+    fn distance(&self) -> f32 {
+        self.eye.distance(self.target)
+    }
+
+    /// Orientation with `fallback` providing the basis `forward` and `up`
+    /// leave undefined: its up when they're parallel and all of it when the
+    /// eye sits on the target.
+    fn rotation(&self, fallback: Option<Quat>) -> Option<Quat> {
+        let forward = (self.target - self.eye).normalize_or_zero();
+        if forward == Vec3::ZERO {
+            return fallback;
+        }
+        let mut right = forward.cross(self.up).normalize_or_zero();
+        if right == Vec3::ZERO {
+            right = forward.cross(fallback? * Vec3::Y).normalize_or_zero();
+        }
+        if right == Vec3::ZERO {
+            return fallback;
+        }
+        Some(Quat::from_mat3(&Mat3::from_cols(
+            right,
+            right.cross(forward),
+            -forward,
+        )))
+    }
+
+    fn from_parts(time: f32, eye: Vec3, rotation: Quat, distance: f32, fovy: f32) -> Self {
+        Self::new(
+            time,
+            eye,
+            eye + rotation * Vec3::NEG_Z * distance,
+            rotation * Vec3::Y,
+            fovy,
+        )
+    }
+}
+
+/// Curve through the keyframe positions.
+///
+/// Orientation is interpolated on the sphere of rotations, the distance to
+/// the target and the field of view follow the same curve as the eye.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Passes through every keyframe, tangents account for uneven spacing
+    /// in time. Orientation follows a squad spline.
+    #[default]
+    CatmullRom,
+    /// A single Bézier curve using the keyframes as control points, smoother
+    /// but only the first and last keyframes are reached.
+    Bezier,
+}
+
+/// Keyframed camera path, e.g. for fly-through videos.
+///
+/// Record poses with [`CameraAnimation::record`] and either play them back
+/// with an [`AnimationController`](crate::AnimationController) or call
+/// [`CameraAnimation::apply`] from [`Demo::update`](crate::Demo::update)
+/// with a time derived from the frame number when rendering offline.
+#[derive(Debug, Clone, Default)]
+pub struct CameraAnimation {
+    keyframes: Vec<CameraKeyframe>,
+    pub interpolation: Interpolation,
+    /// Wrap time past the last keyframe back to the first one.
+    pub looping: bool,
+}
+
+impl CameraAnimation {
+    pub fn new(keyframes: impl IntoIterator<Item = CameraKeyframe>) -> Self {
+        let mut animation = Self::default();
+        for keyframe in keyframes {
+            animation.add_keyframe(keyframe);
+        }
+        animation
+    }
+
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Keyframes sorted by time.
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    /// Inserts `keyframe` in time order, replacing one at the same time.
+    pub fn add_keyframe(&mut self, keyframe: CameraKeyframe) {
+        match self
+            .keyframes
+            .binary_search_by(|probe| probe.time.total_cmp(&keyframe.time))
+        {
+            Ok(index) => self.keyframes[index] = keyframe,
+            Err(index) => self.keyframes.insert(index, keyframe),
+        }
+    }
+
+    /// Adds the current pose of `camera` at `time`.
+    pub fn record(&mut self, time: f32, camera: &Camera) {
+        self.add_keyframe(CameraKeyframe::from_camera(time, camera));
+    }
+
+    pub fn remove_keyframe(&mut self, index: usize) -> CameraKeyframe {
+        self.keyframes.remove(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    pub fn start_time(&self) -> f32 {
+        self.keyframes.first().map_or(0., |keyframe| keyframe.time)
+    }
+
+    pub fn end_time(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.end_time() - self.start_time()
+    }
+
+    /// Time of `frame` when rendering at `fps`, for deterministic playback.
+    ///
+    /// `None` if `fps` isn't positive.
+    pub fn frame_time(&self, frame: u32, fps: f32) -> Option<f32> {
+        (fps > 0.).then(|| self.start_time() + frame as f32 / fps)
+    }
+
+    /// Orientation of every keyframe. Degenerate ones keep the basis of the
+    /// closest valid keyframe before them, or after them at the start, and
+    /// neighbours share a hemisphere so interpolation takes the short way.
+    fn rotations(&self) -> Vec<Quat> {
+        let mut rotations: Vec<Option<Quat>> = Vec::with_capacity(self.keyframes.len());
+        for keyframe in &self.keyframes {
+            let previous = rotations.last().copied().flatten();
+            rotations.push(keyframe.rotation(previous));
+        }
+        let mut next = None;
+        for (keyframe, rotation) in self.keyframes.iter().zip(&mut rotations).rev() {
+            if rotation.is_none() {
+                *rotation = keyframe.rotation(next);
+            }
+            next = rotation.or(next);
+        }
+
+        let mut previous = Quat::IDENTITY;
+        rotations
+            .into_iter()
+            .map(|rotation| {
+                let rotation = rotation.unwrap_or(previous);
+                previous = if rotation.dot(previous) < 0. {
+                    -rotation
+                } else {
+                    rotation
+                };
+                previous
+            })
+            .collect()
+    }
+
+    /// Interpolated pose at `time`, clamped to the keyframe range unless
+    /// looping. `None` without keyframes.
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let (first, last) = (self.keyframes.first()?, self.keyframes.last()?);
+        let duration = self.duration();
+        let time = if self.looping && duration > 0. {
+            first.time + (time - first.time).rem_euclid(duration)
+        } else {
+            time.clamp(first.time, last.time)
+        };
+        if duration <= 0. {
+            return Some(CameraKeyframe { time, ..*first });
+        }
+
+        Some(match self.interpolation {
+            Interpolation::CatmullRom => self.sample_catmull_rom(time),
+            Interpolation::Bezier => self.sample_bezier(time, (time - first.time) / duration),
+        })
+    }
+
+    /// Moves `camera` to the pose at `time`, returns `false` without keyframes.
+    pub fn apply(&self, time: f32, camera: &mut Camera) -> bool {
+        self.sample(time)
+            .map(|keyframe| keyframe.apply(camera))
+            .is_some()
+    }
+
+    fn sample_catmull_rom(&self, time: f32) -> CameraKeyframe {
+        let keys = &self.keyframes;
+        let segment = keys
+            .partition_point(|keyframe| keyframe.time <= time)
+            .clamp(1, keys.len() - 1)
+            - 1;
+        let (k1, k2) = (&keys[segment], &keys[segment + 1]);
+        let k0 = &keys[segment.saturating_sub(1)];
+        let k3 = &keys[(segment + 2).min(keys.len() - 1)];
+
+        let span = k2.time - k1.time;
+        let u = if span > 0. {
+            (time - k1.time) / span
+        } else {
+            0.
+        };
+        // Finite difference tangents in units per second, scaled to the segment
+        let tangent =
+            |prev: &CameraKeyframe, next: &CameraKeyframe, value: fn(&CameraKeyframe) -> Vec3| {
+                let dt = next.time - prev.time;
+                if dt > 0. {
+                    (value(next) - value(prev)) * (span / dt)
+                } else {
+                    Vec3::ZERO
+                }
+            };
+        let curve = |value: fn(&CameraKeyframe) -> Vec3| {
+            hermite(
+                value(k1),
+                value(k2),
+                tangent(k0, k2, value),
+                tangent(k1, k3, value),
+                u,
+            )
+        };
+
+        let eye = curve(|keyframe| keyframe.eye);
+        let scalars = curve(|keyframe| Vec3::new(keyframe.distance(), keyframe.fovy, 0.));
+
+        // Squad keeps the angular velocity continuous across keyframes
+        let rotations = self.rotations();
+        let rotation = |index: usize| rotations[index.min(keys.len() - 1)];
+        let (q1, q2) = (rotation(segment), rotation(segment + 1));
+        let s1 = squad_control(rotation(segment.saturating_sub(1)), q1, q2);
+        let s2 = squad_control(q1, q2, rotation(segment + 2));
+        let rotation = q1.slerp(q2, u).slerp(s1.slerp(s2, u), 2. * u * (1. - u));
+        CameraKeyframe::from_parts(time, eye, rotation, scalars.x.max(0.), scalars.y)
+    }
+
+    fn sample_bezier(&self, time: f32, t: f32) -> CameraKeyframe {
+        let eye = de_casteljau(self.keyframes.iter().map(|keyframe| keyframe.eye), t);
+        let distance = de_casteljau(self.keyframes.iter().map(CameraKeyframe::distance), t);
+        let fovy = de_casteljau(self.keyframes.iter().map(|keyframe| keyframe.fovy), t);
+
+        // Same construction on the sphere of rotations
+        let mut rotations = self.rotations();
+        for level in (1..rotations.len()).rev() {
+            for i in 0..level {
+                rotations[i] = rotations[i].slerp(rotations[i + 1], t);
+            }
+        }
+        CameraKeyframe::from_parts(time, eye, rotations[0], distance, fovy)
+    }
+}
+
+/// Cubic Hermite spline between `p0` and `p1` at `u` in `[0, 1]`.
+fn hermite<T>(p0: T, p1: T, m0: T, m1: T, u: f32) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    let (u2, u3) = (u * u, u * u * u);
+    p0 * (2. * u3 - 3. * u2 + 1.)
+        + m0 * (u3 - 2. * u2 + u)
+        + p1 * (3. * u2 - 2. * u3)
+        + m1 * (u3 - u2)
+}
+
+/// Inner control point of a squad segment at `q1`, from its neighbours.
+fn squad_control(q0: Quat, q1: Quat, q2: Quat) -> Quat {
+    let inverse = q1.inverse();
+    let next = (inverse * q2).to_scaled_axis();
+    let prev = (inverse * q0).to_scaled_axis();
+    q1 * Quat::from_scaled_axis(-(next + prev) / 4.)
+}
+
+/// Point of the Bézier curve with `points` as control points at `t`.
+fn de_casteljau<T>(points: impl Iterator<Item = T>, t: f32) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    let mut points: Vec<T> = points.collect();
+    for level in (1..points.len()).rev() {
+        for i in 0..level {
+            points[i] = points[i] + (points[i + 1] - points[i]) * t;
+        }
+    }
+    points[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation() -> CameraAnimation {
+        CameraAnimation::new([
+            CameraKeyframe::new(1., Vec3::new(0., 0., 5.), Vec3::ZERO, Vec3::Y, 1.),
+            CameraKeyframe::new(2., Vec3::new(5., 1., 0.), Vec3::ZERO, Vec3::Y, 1.2),
+            CameraKeyframe::new(4., Vec3::new(0., 2., -5.), Vec3::X, Vec3::Y, 0.8),
+        ])
+    }
+
+    fn assert_pose_eq(a: CameraKeyframe, b: CameraKeyframe) {
+        assert!(a.eye.abs_diff_eq(b.eye, 1e-4), "{a:?} != {b:?}");
+        assert!(a.target.abs_diff_eq(b.target, 1e-4), "{a:?} != {b:?}");
+        // Samples carry the camera up, keyframes any up in the same plane
+        let right = |k: CameraKeyframe| (k.target - k.eye).cross(k.up).normalize();
+        assert!(right(a).abs_diff_eq(right(b), 1e-4), "{a:?} != {b:?}");
+        assert!((a.fovy - b.fovy).abs() < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn sample_reaches_endpoints() {
+        for interpolation in [Interpolation::CatmullRom, Interpolation::Bezier] {
+            let animation = animation().with_interpolation(interpolation);
+            let keys = animation.keyframes();
+            assert_pose_eq(animation.sample(1.).unwrap(), keys[0]);
+            assert_pose_eq(animation.sample(4.).unwrap(), keys[2]);
+            // Clamped outside of the keyframes
+            assert_pose_eq(animation.sample(-3.).unwrap(), keys[0]);
+            assert_pose_eq(animation.sample(9.).unwrap(), keys[2]);
+        }
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_keyframes() {
+        let animation = animation();
+        assert_pose_eq(animation.sample(2.).unwrap(), animation.keyframes()[1]);
+    }
+
+    #[test]
+    fn sample_loops() {
+        let animation = animation().with_looping(true);
+        assert_pose_eq(
+            animation.sample(2.5).unwrap(),
+            animation.sample(5.5).unwrap(),
+        );
+        assert_pose_eq(
+            animation.sample(0.5).unwrap(),
+            animation.sample(3.5).unwrap(),
+        );
+        assert!((animation.sample(5.5).unwrap().time - 2.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sample_without_keyframes() {
+        assert!(CameraAnimation::default().sample(1.).is_none());
+    }
+
+    #[test]
+    fn up_parallel_to_forward_keeps_previous_basis() {
+        let animation = CameraAnimation::new([
+            CameraKeyframe::new(0., Vec3::new(0., 0., 5.), Vec3::ZERO, Vec3::X, 1.),
+            CameraKeyframe::new(1., Vec3::new(0., 5., 0.), Vec3::ZERO, Vec3::Y, 1.),
+        ]);
+        let rotations = animation.rotations();
+        assert!((rotations[1] * Vec3::NEG_Z).abs_diff_eq(Vec3::NEG_Y, 1e-5));
+        // The up of the first keyframe carries over instead of the identity
+        assert!((rotations[1] * Vec3::Y).abs_diff_eq(Vec3::X, 1e-5));
+    }
+
+    #[test]
+    fn frame_time_rejects_zero_fps() {
+        assert_eq!(animation().frame_time(1, 0.), None);
+        assert_eq!(animation().frame_time(0, -24.), None);
+    }
+}
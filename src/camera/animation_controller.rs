@@ -0,0 +1,154 @@
+use crate::{Camera, CameraAnimation, CameraController};
+
+/// How an [`AnimationController`] advances time.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Playback {
+    /// Follows the wall clock.
+    #[default]
+    RealTime,
+    /// One step of `1 / fps` seconds per update, which [`run`](crate::run)
+    /// makes once per frame in [`RenderMode::Continuous`](crate::RenderMode).
+    /// Playback holds still while `fps` isn't positive, see
+    /// [`Playback::fixed_step`].
+    ///
+    /// For frame exact offline renders call [`CameraAnimation::apply`] with
+    /// [`CameraAnimation::frame_time`] from [`Demo::update`](crate::Demo::update)
+    /// instead.
+    FixedStep { fps: f32 },
+}
+
+impl Playback {
+    /// Fixed step playback, `None` unless `fps` is positive.
+    pub fn fixed_step(fps: f32) -> Option<Self> {
+        (fps > 0.).then_some(Self::FixedStep { fps })
+    }
+
+    /// Seconds to advance after a frame that took `dt`.
+    fn step(self, dt: f32) -> f32 {
+        match self {
+            Self::RealTime => dt,
+            Self::FixedStep { fps } if fps > 0. => 1. / fps,
+            Self::FixedStep { .. } => 0.,
+        }
+    }
+}
+
+/// Plays a [`CameraAnimation`] back, ignoring user input.
+#[derive(Debug, Clone)]
+pub struct AnimationController {
+    pub animation: CameraAnimation,
+    pub playback: Playback,
+    /// Multiplies the playback speed.
+    pub speed: f32,
+
+    time: f32,
+    playing: bool,
+}
+
+impl AnimationController {
+    pub fn new(animation: CameraAnimation) -> Self {
+        Self {
+            time: animation.start_time(),
+            animation,
+            playback: Playback::default(),
+            speed: 1.,
+            playing: true,
+        }
+    }
+
+    pub fn with_playback(mut self, playback: Playback) -> Self {
+        self.playback = playback;
+        self
+    }
+
+    /// Current time on the animation timeline.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    /// Plays from the first keyframe.
+    pub fn restart(&mut self) {
+        self.time = self.animation.start_time();
+        self.playing = true;
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Whether playback reached the last keyframe of a non looping animation.
+    pub fn is_finished(&self) -> bool {
+        !self.animation.looping && self.time >= self.animation.end_time()
+    }
+}
+
+impl CameraController for AnimationController {
+    /// Moves the camera to the current time, then advances it.
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        self.animation.apply(self.time, camera);
+        if self.is_finished() {
+            self.playing = false;
+            return;
+        }
+        self.time += self.playback.step(dt) * self.speed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::CameraKeyframe;
+
+    use glam::Vec3;
+
+    fn controller(playback: Playback) -> AnimationController {
+        AnimationController::new(CameraAnimation::new([
+            CameraKeyframe::new(0., Vec3::new(0., 0., 5.), Vec3::ZERO, Vec3::Y, 1.),
+            CameraKeyframe::new(1., Vec3::new(5., 0., 0.), Vec3::ZERO, Vec3::Y, 1.),
+        ]))
+        .with_playback(playback)
+    }
+
+    #[test]
+    fn fixed_step_needs_positive_fps() {
+        assert_eq!(
+            Playback::fixed_step(30.),
+            Some(Playback::FixedStep { fps: 30. })
+        );
+        assert_eq!(Playback::fixed_step(0.), None);
+        assert_eq!(Playback::fixed_step(f32::NAN), None);
+    }
+
+    #[test]
+    fn fixed_step_ignores_frame_time() {
+        let mut camera = Camera::new(2., 0.3, 0.5, Vec3::ZERO, 1.);
+        let mut controller = controller(Playback::FixedStep { fps: 4. });
+        controller.update(&mut camera, 10.);
+        assert_eq!(controller.time(), 0.25);
+    }
+
+    #[test]
+    fn zero_fps_holds_still() {
+        let mut camera = Camera::new(2., 0.3, 0.5, Vec3::ZERO, 1.);
+        let mut controller = controller(Playback::FixedStep { fps: 0. });
+        controller.update(&mut camera, 0.1);
+        assert_eq!(controller.time(), 0.);
+        assert!(controller.is_playing());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{Camera, CameraController, Input, InputEvent};
+
+use glam::{Mat3, Quat, Vec2, Vec3};
+use winit::{dpi::PhysicalSize, event::MouseButton};
+
+/// Trackball navigation around [`Camera::target`].
+///
+/// Dragging with the left button rolls a virtual sphere under the cursor, so
+/// the view can go over the poles. The right or middle button pans the
+/// target in the view plane and the wheel dollies towards the point under
+/// the cursor.
+#[derive(Debug, Clone)]
+pub struct ArcballController {
+    /// Fraction of the distance to the target covered by one wheel step.
+    pub dolly_speed: f32,
+
+    window_size: Vec2,
+    cursor: Option<Vec2>,
+    rotating: bool,
+    panning: bool,
+}
+
+impl Default for ArcballController {
+    fn default() -> Self {
+        Self {
+            dolly_speed: 0.1,
+
+            window_size: Vec2::ONE,
+            cursor: None,
+            rotating: false,
+            panning: false,
+        }
+    }
+}
+
+impl ArcballController {
+    pub fn new(window_size: PhysicalSize<u32>) -> Self {
+        let mut controller = Self::default();
+        controller.set_window_size(window_size);
+        controller
+    }
+
+    pub fn set_window_size(&mut self, PhysicalSize { width, height }: PhysicalSize<u32>) {
+        self.window_size = Vec2::new(width as f32, height as f32).max(Vec2::ONE);
+    }
+
+    /// Cursor position in `[-1, 1]`, y up.
+    fn ndc(&self, cursor: Vec2) -> Vec2 {
+        let ndc = cursor / self.window_size * 2. - 1.;
+        Vec2::new(ndc.x, -ndc.y)
+    }
+
+    /// Point of the unit sphere under the cursor in camera space, positions
+    /// outside the sphere slide along its rim.
+    fn sphere_point(&self, cursor: Vec2) -> Vec3 {
+        let mut p = self.ndc(cursor);
+        p.x *= self.window_size.x / self.window_size.y;
+        let length_squared = p.length_squared();
+        if length_squared <= 1. {
+            p.extend((1. - length_squared).sqrt())
+        } else {
+            p.normalize().extend(0.)
+        }
+    }
+
+    fn rotate(&self, camera: &mut Camera, from: Vec2, to: Vec2) {
+        let (from, to) = (self.sphere_point(from), self.sphere_point(to));
+        if from == to {
+            return;
+        }
+        // The scene follows the cursor, so the camera turns the other way
+        let basis = Quat::from_mat3(&Mat3::from_cols(
+            camera.right(),
+            camera.camera_up(),
+            -camera.forward(),
+        ));
+        let rotation = basis * Quat::from_rotation_arc(to, from) * basis.inverse();
+        camera.set_orientation(rotation * camera.forward(), rotation * camera.camera_up());
+    }
+
+    /// Half size of the view at the target distance.
+    fn half_extent(&self, camera: &Camera) -> Vec2 {
+        let half_height = camera.zoom * (camera.fovy / 2.).tan();
+        Vec2::new(half_height * camera.aspect, half_height)
+    }
+
+    fn pan(&self, camera: &mut Camera, delta: Vec2) {
+        let world_per_pixel = 2. * self.half_extent(camera).y / self.window_size.y;
+        camera.translate(
+            (-camera.right() * delta.x + camera.camera_up() * delta.y) * world_per_pixel,
+        );
+    }
+
+    fn dolly(&self, camera: &mut Camera, steps: f32) {
+        let factor = (1. - self.dolly_speed.clamp(0.01, 0.9)).powf(steps);
+        let zoom = camera.zoom;
+        camera.set_zoom(zoom * factor);
+        // Clamped zoom keeps the cursor anchored only as far as it went
+        let factor = camera.zoom / zoom;
+
+        let Some(cursor) = self.cursor else {
+            return;
+        };
+        // Point under the cursor on the plane through the target, an
+        // orthographic view shrinks around it the same way
+        let ndc = self.ndc(cursor);
+        let extent = self.half_extent(camera) / factor;
+        let offset = camera.right() * ndc.x * extent.x + camera.camera_up() * ndc.y * extent.y;
+        camera.translate(offset * (1. - factor));
+    }
+}
+
+impl CameraController for ArcballController {
+    fn resize(&mut self, width: u32, height: u32) {
+        self.set_window_size(PhysicalSize::new(width, height));
+    }
+
+    fn release(&mut self) {
+        self.rotating = false;
+        self.panning = false;
+    }
+
+    fn input(&mut self, camera: &mut Camera, event: &InputEvent, _: &Input) {
+        match *event {
+            InputEvent::MouseButton { button, pressed } => match button {
+                MouseButton::Left => self.rotating = pressed,
+                MouseButton::Right | MouseButton::Middle => self.panning = pressed,
+                _ => {}
+            },
+            InputEvent::CursorMoved { x, y } => {
+                let cursor = Vec2::new(x as f32, y as f32);
+                if let Some(last) = self.cursor {
+                    if self.rotating {
+                        self.rotate(camera, last, cursor);
+                    } else if self.panning {
+                        self.pan(camera, cursor - last);
+                    }
+                }
+                self.cursor = Some(cursor);
+            }
+            InputEvent::CursorLeft => self.cursor = None,
+            InputEvent::MouseWheel(delta) => self.dolly(camera, delta.lines()),
+            InputEvent::Focused(false) => self.release(),
+            _ => {}
+        }
+    }
+
+    fn is_interacting(&self) -> bool {
+        self.rotating || self.panning
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{Camera, CameraAnimation, CameraKeyframe, Error, Projection};
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+
+/// Everything needed to reproduce what a [`Camera`] sees, independent of the
+/// window size.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraView {
+    pub eye: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    /// Vertical field of view in radians.
+    pub fovy: f32,
+    pub znear: f32,
+    pub zfar: f32,
+    #[serde(default)]
+    pub projection: Projection,
+    #[serde(default)]
+    pub reverse_z: bool,
+}
+
+impl CameraView {
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self {
+            eye: camera.eye,
+            target: camera.target,
+            up: camera.camera_up(),
+            fovy: camera.fovy,
+            znear: camera.znear,
+            zfar: camera.zfar,
+            projection: camera.projection,
+            reverse_z: camera.reverse_z,
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        self.apply_projection(camera);
+        self.keyframe(0.).apply(camera);
+    }
+
+    /// Everything but the pose, which may be clamped on the way.
+    fn apply_projection(&self, camera: &mut Camera) {
+        camera.set_projection(self.projection);
+        camera.set_reverse_z(self.reverse_z);
+        camera.set_clip_planes(self.znear, self.zfar);
+    }
+
+    pub fn keyframe(&self, time: f32) -> CameraKeyframe {
+        CameraKeyframe::new(time, self.eye, self.target, self.up, self.fovy)
+    }
+}
+
+/// Named [`CameraView`], optionally bound to a slot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    /// Slot from 0 to 9 recalled and stored by the bookmark actions of
+    /// [`Input`](crate::Input) in [`run`](crate::run), digits and `Ctrl` +
+    /// digit by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u8>,
+    pub view: CameraView,
+}
+
+/// Camera views shared through a JSON file, usually next to the dataset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CameraBookmarks {
+    bookmarks: Vec<Bookmark>,
+}
+
+impl CameraBookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bookmark file of a dataset, `bonsai.raw` keeps its views in
+    /// `bonsai.views.json`.
+    pub fn path_for(dataset: impl AsRef<Path>) -> PathBuf {
+        dataset.as_ref().with_extension("views.json")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Bookmark> {
+        self.bookmarks.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.name == name)
+    }
+
+    pub fn by_slot(&self, slot: u8) -> Option<&Bookmark> {
+        self.bookmarks
+            .iter()
+            .find(|bookmark| bookmark.slot == Some(slot))
+    }
+
+    /// Adds `bookmark`, replacing the one with the same name and taking its
+    /// slot away from any other.
+    pub fn insert(&mut self, bookmark: Bookmark) {
+        if bookmark.slot.is_some() {
+            for other in &mut self.bookmarks {
+                if other.slot == bookmark.slot {
+                    other.slot = None;
+                }
+            }
+        }
+        match self
+            .bookmarks
+            .iter_mut()
+            .find(|other| other.name == bookmark.name)
+        {
+            Some(other) => *other = bookmark,
+            None => self.bookmarks.push(bookmark),
+        }
+    }
+
+    /// Stores `view` in `slot`, keeping the name of the bookmark already there.
+    pub fn store_slot(&mut self, slot: u8, view: CameraView) {
+        let name = self
+            .by_slot(slot)
+            .map_or_else(|| format!("View {slot}"), |bookmark| bookmark.name.clone());
+        self.insert(Bookmark {
+            name,
+            slot: Some(slot),
+            view,
+        });
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Bookmark> {
+        let index = self
+            .bookmarks
+            .iter()
+            .position(|bookmark| bookmark.name == name)?;
+        Some(self.bookmarks.remove(index))
+    }
+}
+
+/// Eased move of the camera to a [`CameraView`].
+#[derive(Debug, Clone)]
+pub(crate) struct CameraTransition {
+    path: CameraAnimation,
+    elapsed: f32,
+    duration: f32,
+}
+
+impl CameraTransition {
+    /// Switches projection and clip planes at once, the pose follows over
+    /// `duration` seconds.
+    pub(crate) fn new(camera: &mut Camera, view: &CameraView, duration: f32) -> Self {
+        let from = CameraKeyframe::from_camera(0., camera);
+        view.apply_projection(camera);
+        from.apply(camera);
+        Self {
+            path: CameraAnimation::new([from, view.keyframe(1.)]),
+            elapsed: 0.,
+            duration,
+        }
+    }
+
+    /// Moves `camera` `dt` seconds further, returns `true` once it arrived.
+    pub(crate) fn advance(&mut self, camera: &mut Camera, dt: f32) -> bool {
+        self.elapsed += dt;
+        let t = (self.elapsed / self.duration.max(f32::EPSILON)).min(1.);
+        let eased = t * t * (3. - 2. * t);
+        self.path.apply(eased, camera);
+        t >= 1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(x: f32) -> CameraView {
+        CameraView {
+            eye: Vec3::new(x, 1., 2.),
+            target: Vec3::ZERO,
+            up: Vec3::Y,
+            fovy: 1.,
+            znear: 0.1,
+            zfar: 50.,
+            projection: Projection::Orthographic,
+            reverse_z: true,
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut bookmarks = CameraBookmarks::new();
+        bookmarks.store_slot(1, view(1.));
+        bookmarks.insert(Bookmark {
+            name: "Overview".into(),
+            slot: None,
+            view: view(2.),
+        });
+        let json = serde_json::to_string(&bookmarks).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CameraBookmarks>(&json).unwrap(),
+            bookmarks
+        );
+    }
+
+    #[test]
+    fn store_slot_keeps_name_and_moves_slot() {
+        let mut bookmarks = CameraBookmarks::new();
+        bookmarks.insert(Bookmark {
+            name: "Leaves".into(),
+            slot: Some(3),
+            view: view(1.),
+        });
+        bookmarks.store_slot(3, view(2.));
+        assert_eq!(bookmarks.get("Leaves").unwrap().view, view(2.));
+
+        bookmarks.insert(Bookmark {
+            name: "Trunk".into(),
+            slot: Some(3),
+            view: view(3.),
+        });
+        assert_eq!(bookmarks.get("Leaves").unwrap().slot, None);
+        assert_eq!(bookmarks.by_slot(3).unwrap().name, "Trunk");
+    }
+
+    #[test]
+    fn projection_defaults_when_missing() {
+        let json = r#"{"bookmarks":[{"name":"A","view":{"eye":[0,0,1],"target":[0,0,0],"up":[0,1,0],"fovy":1,"znear":0.1,"zfar":100}}]}"#;
+        let bookmarks: CameraBookmarks = serde_json::from_str(json).unwrap();
+        let view = bookmarks.get("A").unwrap().view;
+        assert_eq!(view.projection, Projection::default());
+        assert!(!view.reverse_z);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{Camera, Input, InputEvent};
+
+/// Turns input into camera motion, owned by [`Context`](crate::Context).
+///
+/// [`run`](crate::run) forwards window input, device input while the window
+/// is focused, and calls [`CameraController::update`] once per iteration of
+/// the event loop. Input and time steps come from the recording when
+/// replaying one.
+pub trait CameraController {
+    /// Called after `input` tracked `event`, so actions and axes of its
+    /// [`InputMap`](crate::InputMap) are up to date.
+    fn input(&mut self, _camera: &mut Camera, _event: &InputEvent, _input: &Input) {}
+    /// Time based motion over `dt` seconds.
+    fn update(&mut self, _camera: &mut Camera, _dt: f32) {}
+    /// Called with the window size when installed and after every resize.
+    fn resize(&mut self, _width: u32, _height: u32) {}
+    /// Forgets held keys and buttons, e.g. when the controller is replaced.
+    fn release(&mut self) {}
+    /// Whether the user is moving the camera, lowers the quality with
+    /// [`InteractiveLod`](crate::InteractiveLod).
+    fn is_interacting(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{Camera, CameraController, Input, InputEvent};
+
+use glam::Vec3;
+use winit::event::MouseButton;
+
+/// First person navigation along the [`Input::FLY_FORWARD`],
+/// [`Input::FLY_RIGHT`] and [`Input::FLY_UP`] axes, WASD and Q/E by default.
+/// Dragging with the left button looks around and the wheel changes the
+/// speed.
+///
+/// [`Input::FLY_FAST`] (Shift) moves faster and [`Input::FLY_SLOW`] (Ctrl)
+/// slower. Motion is scaled by the frame time passed to
+/// [`CameraController::update`].
+#[derive(Debug, Clone)]
+pub struct FlyController {
+    /// Units per second.
+    pub speed: f32,
+    /// Radians per pixel of mouse motion.
+    pub look_sensitivity: f32,
+    pub fast_multiplier: f32,
+    pub slow_multiplier: f32,
+
+    /// Right, up and forward axes of the last input.
+    movement: Vec3,
+    fast: bool,
+    slow: bool,
+    looking: bool,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self {
+            speed: 1.,
+            look_sensitivity: 0.0025,
+            fast_multiplier: 4.,
+            slow_multiplier: 0.25,
+
+            movement: Vec3::ZERO,
+            fast: false,
+            slow: false,
+            looking: false,
+        }
+    }
+}
+
+impl FlyController {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            ..Default::default()
+        }
+    }
+
+    /// Whether a movement key is held, so frames keep coming.
+    pub fn is_moving(&self) -> bool {
+        self.movement != Vec3::ZERO
+    }
+}
+
+impl CameraController for FlyController {
+    fn release(&mut self) {
+        self.movement = Vec3::ZERO;
+        self.fast = false;
+        self.slow = false;
+        self.looking = false;
+    }
+
+    fn input(&mut self, camera: &mut Camera, event: &InputEvent, input: &Input) {
+        match *event {
+            InputEvent::Key { .. } => {
+                self.movement = Vec3::new(
+                    input.axis(Input::FLY_RIGHT),
+                    input.axis(Input::FLY_UP),
+                    input.axis(Input::FLY_FORWARD),
+                );
+                self.fast = input.is_active(Input::FLY_FAST);
+                self.slow = input.is_active(Input::FLY_SLOW);
+            }
+            InputEvent::MouseButton {
+                button: MouseButton::Left,
+                pressed,
+            } => self.looking = pressed,
+            InputEvent::MouseWheel(delta) => {
+                self.speed = (self.speed * 1.1f32.powf(delta.lines())).clamp(0.01, 1000.);
+            }
+            InputEvent::MouseMotion { dx, dy } if self.looking => {
+                camera.look(
+                    -dx as f32 * self.look_sensitivity,
+                    -dy as f32 * self.look_sensitivity,
+                );
+            }
+            // Keys released while unfocused never arrive
+            InputEvent::Focused(false) => self.release(),
+            _ => {}
+        }
+    }
+
+    /// Moves the camera along the held axes over `dt` seconds.
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        let forward = camera.forward();
+        let right = forward.cross(Vec3::Y).normalize_or_zero();
+        let direction =
+            right * self.movement.x + Vec3::Y * self.movement.y + forward * self.movement.z;
+        if direction == Vec3::ZERO {
+            return;
+        }
+
+        let mut speed = self.speed;
+        if self.fast {
+            speed *= self.fast_multiplier;
+        }
+        if self.slow {
+            speed *= self.slow_multiplier;
+        }
+        camera.translate(direction.normalize() * speed * dt);
+    }
+
+    fn is_interacting(&self) -> bool {
+        self.looking || self.is_moving()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{AxisBinding, Binding, InputMap};
+
+    use winit::{dpi::PhysicalSize, keyboard::KeyCode};
+
+    fn press(input: &mut Input, code: KeyCode) -> InputEvent {
+        let event = InputEvent::Key {
+            code,
+            pressed: true,
+            repeat: false,
+        };
+        input.update(&event, PhysicalSize::new(100, 100));
+        event
+    }
+
+    #[test]
+    fn moves_along_rebound_axes() {
+        let mut map = InputMap::default();
+        map.axes.insert(
+            Input::FLY_FORWARD.into(),
+            vec![AxisBinding::new(
+                Binding::key(KeyCode::KeyI),
+                Binding::key(KeyCode::KeyK),
+            )],
+        );
+        let mut input = Input::with_map(map);
+        let mut camera = Camera::new(2., 0.3, 0.5, Vec3::ZERO, 1.);
+        let mut controller = FlyController::new(1.);
+
+        let event = press(&mut input, KeyCode::KeyW);
+        controller.input(&mut camera, &event, &input);
+        assert!(!controller.is_moving());
+
+        let start = camera.eye;
+        let event = press(&mut input, KeyCode::KeyI);
+        controller.input(&mut camera, &event, &input);
+        assert!(controller.is_moving());
+        controller.update(&mut camera, 0.5);
+        assert!((camera.eye - (start + camera.forward() * 0.5)).length() < 1e-5);
+    }
+}
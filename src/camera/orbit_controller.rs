@@ -0,0 +1,60 @@
+use crate::{Camera, CameraController, Input, InputEvent, ScrollDelta};
+
+use winit::event::MouseButton;
+
+/// Drag to orbit around [`Camera::target`], scroll to zoom.
+#[derive(Debug, Clone)]
+pub struct OrbitController {
+    /// Radians per pixel of mouse motion.
+    pub rotate_speed: f32,
+    /// Zoom per scrolled line or pixel.
+    pub zoom_speed: f32,
+    /// Button that starts dragging.
+    pub button: MouseButton,
+
+    dragging: bool,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            rotate_speed: 0.0025,
+            zoom_speed: 0.002,
+            button: MouseButton::Left,
+
+            dragging: false,
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn input(&mut self, camera: &mut Camera, event: &InputEvent, _: &Input) {
+        match *event {
+            InputEvent::MouseButton { button, pressed } if button == self.button => {
+                self.dragging = pressed;
+            }
+            InputEvent::DeviceWheel(delta) => {
+                let scroll_amount = -match delta {
+                    ScrollDelta::Line { y, .. } => y,
+                    ScrollDelta::Pixel { y, .. } => y as f32,
+                };
+                camera.add_zoom(scroll_amount * self.zoom_speed);
+            }
+            InputEvent::MouseMotion { dx, dy } if self.dragging => {
+                camera.add_yaw(-dx as f32 * self.rotate_speed);
+                camera.add_pitch(dy as f32 * self.rotate_speed);
+            }
+            // Buttons released while unfocused never arrive
+            InputEvent::Focused(false) => self.release(),
+            _ => {}
+        }
+    }
+
+    fn release(&mut self) {
+        self.dragging = false;
+    }
+
+    fn is_interacting(&self) -> bool {
+        self.dragging
+    }
+}
@@ -1,80 +1,155 @@
+mod accumulator;
+mod dynamic_resolution;
+mod feedback_texture;
 mod global_ubo;
+mod gpu_timer;
 mod hdr_backbuffer;
+mod image_texture;
+mod input_binding;
+mod interactive_lod;
+mod options;
 #[allow(dead_code)]
 mod pipelines;
 mod present_pipeline;
+mod taa;
+mod tiled_dispatcher;
 mod volume_texture;
 
+pub use dynamic_resolution::DynamicResolution;
+pub use feedback_texture::FeedbackTexture;
 pub use global_ubo::GlobalUniformBinding;
 pub use global_ubo::Uniform;
+pub use gpu_timer::GpuTimer;
 pub use hdr_backbuffer::HdrBackBuffer;
+pub use image_texture::{ImageTexture, TextureOptions};
+pub use input_binding::{js_key_code, InputBinding, InputUniform};
+pub use interactive_lod::InteractiveLod;
+pub use options::{AdapterSelector, ContextOptions};
+pub use present_pipeline::PresentFilter;
+pub use tiled_dispatcher::{TileOffset, TiledDispatcher};
 pub use volume_texture::VolumeTexture;
 
-use crate::utils::frame_counter::FrameCounter;
-use crate::utils::input::Input;
-use crate::{Camera, CameraBinding};
+use crate::utils::input::{Input, InputMap};
+use crate::utils::{halton, recording::FrameTiming};
+use crate::{
+    camera::CameraTransition, Camera, CameraBinding, CameraBookmarks, CameraController, CameraMode,
+    CameraView, Error, InputEvent,
+};
 
-use present_pipeline::PresentPipeline;
+use accumulator::Accumulator;
+use present_pipeline::{PresentPipeline, PresentUniform};
+use taa::Taa;
 use wgpu::StoreOp;
 use winit::{dpi::PhysicalSize, window::Window};
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 pub struct Context {
     adapter: wgpu::Adapter,
     pub device: Arc<wgpu::Device>,
     pub queue: wgpu::Queue,
-    surface: wgpu::Surface,
+    /// `None` only while [`Context::recreate_device`] runs.
+    surface: Option<wgpu::Surface>,
     pub surface_config: wgpu::SurfaceConfiguration,
+    surface_caps: wgpu::SurfaceCapabilities,
+    frame_rate_cap: Option<f32>,
+    pub features: wgpu::Features,
     pub limits: wgpu::Limits,
 
     pub camera: Camera,
     pub camera_binding: CameraBinding,
+    camera_controller: Option<Box<dyn CameraController>>,
+    camera_mode: Option<CameraMode>,
+    camera_transition: Option<CameraTransition>,
+    bookmarks: CameraBookmarks,
+    /// Whether the bookmark file failed to load and must not be overwritten.
+    bookmarks_unreadable: bool,
 
     pub render_backbuffer: HdrBackBuffer,
+    accumulator: Option<Accumulator>,
+    taa: Option<Taa>,
 
     rgb_texture: wgpu::Texture,
 
     pub width: u32,
     pub height: u32,
+    render_scale: f32,
+    /// Backbuffer size of a replayed tick, replaces the scaled window size.
+    replayed_resolution: Option<(u32, u32)>,
+    dynamic_resolution: Option<DynamicResolution>,
+    gpu_timer: Option<GpuTimer>,
+    interactive_lod: Option<InteractiveLod>,
+    interacting: bool,
 
-    timeline: Instant,
+    /// Seconds of [`FrameTiming::dt`] since the start.
+    time: f32,
+    unix_time: f64,
 
     pub global_uniform: Uniform,
     pub global_uniform_binding: GlobalUniformBinding,
+    input: Input,
+    /// Keyboard and mouse state for shaders, see [`InputBinding`].
+    pub input_binding: InputBinding,
 
     present_pipeline: PresentPipeline,
+    present_settings: PresentUniform,
+
+    options: ContextOptions,
+    device_lost: Arc<AtomicBool>,
+
+    render_mode: RenderMode,
+    redraw_requested: AtomicBool,
 }
 
-impl Context {
-    /// Create a new window with a given `window`
-    pub async fn new(window: &Window, camera: Option<Camera>) -> Result<Self, String> {
-        // Create new instance using first-tier backend of WGPU
-        // One of Vulkan + Metal + DX12 + Browser WebGPU
+/// When [`run`](crate::run) redraws the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Every time the event loop goes idle.
+    #[default]
+    Continuous,
+    /// Only after input, a resize, a camera or uniform change, a shader reload
+    /// or [`Context::request_redraw`].
+    OnDemand,
+}
+
+/// Device level objects that have to be recreated together after a device loss.
+struct Gpu {
+    adapter: wgpu::Adapter,
+    device: Arc<wgpu::Device>,
+    queue: wgpu::Queue,
+    surface: wgpu::Surface,
+    surface_caps: wgpu::SurfaceCapabilities,
+    features: wgpu::Features,
+}
+
+impl Gpu {
+    async fn new(
+        window: &Window,
+        options: &ContextOptions,
+        device_lost: &Arc<AtomicBool>,
+    ) -> Result<Self, Error> {
         let instance_desc = wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
+            backends: options.backends,
             ..Default::default()
         };
         let instance = wgpu::Instance::new(instance_desc);
 
         // Create a `surface` represents a platform-specific window
         // onto which rendered images may be presented
-        let surface = unsafe { instance.create_surface(&window) }.unwrap();
+        let surface = unsafe { instance.create_surface(&window) }?;
 
         // Get a handle to a physical device
-        let adapter: wgpu::Adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
-            })
-            .await
-            .ok_or("Failed to create device adapter.".to_string())?;
-
-        // Use default features and limits for your machine
-        let features = adapter.features();
-        let limits = adapter.limits();
-        let surface_format = wgpu::TextureFormat::Bgra8Unorm;
+        let adapter = options.request_adapter(&instance, &surface).await?;
+
+        // Request only what the options ask for
+        let features = options.device_features(&adapter)?;
+        let surface_caps = surface.get_capabilities(&adapter);
 
         // Create the logical device and command queue
         let (device, queue) = adapter
@@ -82,13 +157,62 @@ impl Context {
                 &wgpu::DeviceDescriptor {
                     label: Some("Device Descriptor"),
                     features,
-                    limits: limits.clone(),
+                    limits: options.limits.clone(),
                 },
                 None,
             )
-            .await
-            .map_err(|e| e.to_string())?;
-        let device = Arc::new(device);
+            .await?;
+
+        // wgpu 0.18 has no device lost callback, loss shows up as uncaptured errors
+        let lost = Arc::clone(device_lost);
+        device.on_uncaptured_error(Box::new(move |error| match error {
+            wgpu::Error::OutOfMemory { .. } => {
+                eprintln!("Device out of memory, recreating");
+                lost.store(true, Ordering::Relaxed);
+            }
+            wgpu::Error::Validation {
+                source,
+                description,
+            } if caused_by_device_loss(source.as_ref()) => {
+                eprintln!("Device lost: {description}");
+                lost.store(true, Ordering::Relaxed);
+            }
+            // Other errors are reported instead of aborting from the callback
+            error => eprintln!("wgpu error: {error}"),
+        }));
+
+        Ok(Self {
+            adapter,
+            device: Arc::new(device),
+            queue,
+            surface,
+            surface_caps,
+            features,
+        })
+    }
+}
+
+impl Context {
+    /// Seconds [`Context::go_to_view`] takes to reach the view.
+    pub const CAMERA_TRANSITION: f32 = 0.75;
+
+    /// Create a new window with a given `window`
+    pub async fn new(
+        window: &Window,
+        camera: Option<Camera>,
+        options: &ContextOptions,
+    ) -> Result<Self, Error> {
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let Gpu {
+            adapter,
+            device,
+            queue,
+            surface,
+            surface_caps,
+            features,
+        } = Gpu::new(window, options, &device_lost).await?;
+        let limits = options.limits.clone();
+        let surface_format = choose_surface_format(&surface_caps.formats, options.hdr);
 
         let PhysicalSize { width, height } = window.inner_size();
         let surface_config = wgpu::SurfaceConfiguration {
@@ -96,13 +220,13 @@ impl Context {
             format: surface_format,
             width,
             height,
-            present_mode: wgpu::PresentMode::Fifo,
+            present_mode: choose_present_mode(&surface_caps.present_modes, options.present_mode),
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
             view_formats: vec![],
         };
         surface.configure(&device, &surface_config);
 
-        let camera = camera.unwrap_or_else(|| {
+        let mut camera = camera.unwrap_or_else(|| {
             Camera::new(
                 1.,
                 0.5,
@@ -111,39 +235,157 @@ impl Context {
                 width as f32 / height as f32,
             )
         });
-        let render_backbuffer = HdrBackBuffer::new(&device, HdrBackBuffer::DEFAULT_RESOLUTION);
+        let render_scale = clamp_render_scale(options.render_scale);
+        let render_backbuffer =
+            HdrBackBuffer::new(&device, scaled_resolution(width, height, render_scale));
+        let (render_width, render_height) = render_backbuffer.size();
+        camera.set_aspect(render_width, render_height);
         let rgb_texture = create_rgb_framebuffer(&device, &surface_config);
+        let accumulator = options
+            .accumulation
+            .map(|samples| Accumulator::new(&device, render_backbuffer.size(), samples));
+        let taa = options
+            .taa
+            .then(|| Taa::new(&device, render_backbuffer.size()));
 
-        let present_shader = wgpu::include_wgsl!("../shaders/present.wgsl");
-        let present_pipeline = PresentPipeline::new(&device, surface_format, present_shader);
+        let present_settings = PresentUniform::new(
+            options.paper_white,
+            options.max_luminance,
+            options.present_filter,
+        );
+        let present_pipeline = create_present_pipeline(&device, surface_format, present_settings);
+        let (bookmarks, bookmarks_unreadable) = load_bookmarks(options);
 
         Ok(Self {
             camera,
             camera_binding: CameraBinding::new(&device),
+            camera_controller: Some(CameraMode::Orbit.controller()),
+            camera_mode: Some(CameraMode::Orbit),
+            camera_transition: None,
+            bookmarks,
+            bookmarks_unreadable,
 
             rgb_texture,
 
             render_backbuffer,
+            accumulator,
+            taa,
 
             width,
             height,
+            render_scale,
+            replayed_resolution: None,
+            dynamic_resolution: options.dynamic_resolution,
+            gpu_timer: options
+                .dynamic_resolution
+                .and_then(|_| create_gpu_timer(&device, &queue, features)),
+            interactive_lod: options.interactive_lod,
+            interacting: false,
 
-            timeline: Instant::now(),
+            time: 0.,
+            unix_time: 0.,
 
             present_pipeline,
+            present_settings,
 
             global_uniform: Uniform::default(),
             global_uniform_binding: GlobalUniformBinding::new(&device),
+            input: Input::with_map(load_input_map(options)),
+            input_binding: InputBinding::new(&device),
 
             device,
             adapter,
             queue,
-            surface,
+            surface: Some(surface),
             surface_config,
+            surface_caps,
+            frame_rate_cap: options.frame_rate_cap,
+            features,
             limits,
+
+            options: options.clone(),
+            device_lost,
+
+            render_mode: options.render_mode,
+            redraw_requested: AtomicBool::new(true),
         })
     }
 
+    /// Whether the device was lost and [`Context::recreate_device`] has to be called.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// Flags the device as lost, e.g. after `SurfaceError::OutOfMemory`.
+    pub fn mark_device_lost(&self) {
+        self.device_lost.store(true, Ordering::Relaxed);
+    }
+
+    /// Applies `surface_config`, the surface is only missing after
+    /// [`Context::recreate_device`] failed.
+    fn configure_surface(&self) {
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.surface_config);
+        }
+    }
+
+    /// Recreates the surface, device and queue together with every resource
+    /// owned by the context.
+    ///
+    /// Resources created by a [`Demo`](crate::Demo) belong to the old device
+    /// and must be rebuilt too, see [`Demo::reinit`](crate::Demo::reinit).
+    pub async fn recreate_device(&mut self, window: &Window) -> Result<(), Error> {
+        self.device_lost.store(false, Ordering::Relaxed);
+        // Some backends refuse a second surface for a window that still has one
+        self.surface = None;
+        let Gpu {
+            adapter,
+            device,
+            queue,
+            surface,
+            surface_caps,
+            features,
+        } = Gpu::new(window, &self.options, &self.device_lost).await?;
+
+        self.surface_config.format = choose_surface_format(&surface_caps.formats, self.options.hdr);
+        self.surface_config.present_mode = choose_present_mode(
+            &surface_caps.present_modes,
+            self.surface_config.present_mode,
+        );
+        surface.configure(&device, &self.surface_config);
+
+        self.camera_binding = CameraBinding::new(&device);
+        self.camera.updated = true;
+        self.global_uniform_binding = GlobalUniformBinding::new(&device);
+        self.input_binding = InputBinding::new(&device);
+        self.render_backbuffer = HdrBackBuffer::new(&device, self.render_resolution());
+        self.accumulator = self.accumulator.as_ref().map(|accumulator| {
+            Accumulator::new(
+                &device,
+                self.render_backbuffer.size(),
+                accumulator.max_samples(),
+            )
+        });
+        if self.taa.is_some() {
+            self.taa = Some(Taa::new(&device, self.render_backbuffer.size()));
+        }
+        self.rgb_texture = create_rgb_framebuffer(&device, &self.surface_config);
+        self.present_pipeline =
+            create_present_pipeline(&device, self.surface_config.format, self.present_settings);
+
+        self.gpu_timer = self
+            .dynamic_resolution
+            .and_then(|_| create_gpu_timer(&device, &queue, features));
+
+        self.adapter = adapter;
+        self.device = device;
+        self.queue = queue;
+        self.surface = Some(surface);
+        self.surface_caps = surface_caps;
+        self.features = features;
+        Ok(())
+    }
+
     pub fn get_info(&self) -> RendererInfo {
         let info = self.adapter.get_info();
         RendererInfo {
@@ -189,17 +431,277 @@ impl Context {
         }
     }
 
-    pub fn update(&mut self, frame_counter: &FrameCounter, input: &Input) {
-        self.global_uniform.time = self.timeline.elapsed().as_secs_f32();
-        self.global_uniform.time_delta = frame_counter.time_delta();
-        self.global_uniform.frame = frame_counter.frame_count;
-        self.global_uniform.resolution = [self.width as _, self.height as _];
-        input.process_position(&mut self.global_uniform);
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.present_mode
+    }
+
+    /// Reconfigures the surface with `present_mode`.
+    ///
+    /// Modes the surface doesn't support fall back to `AutoVsync` or `AutoNoVsync`.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        let present_mode = choose_present_mode(&self.surface_caps.present_modes, present_mode);
+        if present_mode != self.surface_config.present_mode {
+            self.surface_config.present_mode = present_mode;
+            self.configure_surface();
+        }
+    }
+
+    pub fn vsync(&self) -> bool {
+        is_vsync(self.surface_config.present_mode)
+    }
+
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.set_present_mode(if vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        });
+    }
+
+    pub fn frame_rate_cap(&self) -> Option<f32> {
+        self.frame_rate_cap
+    }
+
+    /// Limits how often [`run`](crate::run) redraws, `None` or a non positive
+    /// value removes the limit.
+    pub fn set_frame_rate_cap(&mut self, fps: Option<f32>) {
+        self.frame_rate_cap = fps.filter(|fps| *fps > 0.);
+    }
+
+    /// Minimal time between two frames imposed by the frame rate cap.
+    pub fn min_frame_time(&self) -> Option<Duration> {
+        self.frame_rate_cap
+            .map(|fps| Duration::from_secs_f32(1. / fps))
+    }
+
+    /// Whether the surface was configured for HDR (scRGB) output.
+    pub fn is_hdr(&self) -> bool {
+        PresentPipeline::is_hdr_format(self.surface_config.format)
+    }
+
+    /// Luminance in nits the SDR white is mapped to on HDR output.
+    pub fn set_paper_white(&mut self, nits: f32) {
+        self.present_settings.paper_white = nits.max(1.);
+        self.present_pipeline
+            .update_settings(&self.queue, &self.present_settings);
+    }
+
+    /// Peak luminance in nits the HDR output rolls highlights off to.
+    pub fn set_max_luminance(&mut self, nits: f32) {
+        self.present_settings.max_luminance = nits.max(1.);
+        self.present_pipeline
+            .update_settings(&self.queue, &self.present_settings);
+    }
+
+    pub fn render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    pub fn set_render_mode(&mut self, render_mode: RenderMode) {
+        self.render_mode = render_mode;
+        self.request_redraw();
+    }
+
+    /// Asks for another frame, needed in [`RenderMode::OnDemand`] when the
+    /// image changes for reasons the context can't see.
+    pub fn request_redraw(&self) {
+        self.redraw_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the next frame has to be drawn, clears pending requests.
+    pub(crate) fn take_redraw_request(&self) -> bool {
+        let requested = self.redraw_requested.swap(false, Ordering::Relaxed);
+        requested || self.render_mode == RenderMode::Continuous
+    }
+
+    /// Built-in controller in use, `None` for a custom one or none at all.
+    pub fn camera_mode(&self) -> Option<CameraMode> {
+        self.camera_mode
+    }
+
+    /// Replaces the camera controller with a built-in one.
+    pub fn set_camera_mode(&mut self, mode: CameraMode) {
+        self.set_camera_controller(Some(mode.controller()));
+        self.camera_mode = Some(mode);
+    }
+
+    pub fn camera_controller_mut(&mut self) -> Option<&mut (dyn CameraController + 'static)> {
+        self.camera_controller.as_deref_mut()
+    }
+
+    /// Replaces the camera controller, `None` leaves the camera to the demo.
+    ///
+    /// Demos install their own from [`Demo::init`](crate::Demo::init).
+    pub fn set_camera_controller(&mut self, controller: Option<Box<dyn CameraController>>) {
+        if let Some(old) = &mut self.camera_controller {
+            old.release();
+        }
+        self.camera_controller = controller;
+        self.camera_mode = None;
+        if let Some(controller) = &mut self.camera_controller {
+            controller.resize(self.width, self.height);
+        }
+    }
+
+    /// Returns `true` when the backbuffer was resized by [`InteractiveLod`].
+    pub(crate) fn camera_input(&mut self, event: &InputEvent) -> bool {
+        if let Some(controller) = &mut self.camera_controller {
+            controller.input(&mut self.camera, event, &self.input);
+        }
+        self.sync_interaction()
+    }
+
+    /// Advances time based camera motion by `dt` seconds.
+    pub(crate) fn update_camera(&mut self, dt: f32) {
+        // Long pauses between frames shouldn't teleport the camera
+        let dt = dt.min(0.1);
+        if let Some(transition) = &mut self.camera_transition {
+            // Grabbing the camera cancels the transition
+            if self.interacting || transition.advance(&mut self.camera, dt) {
+                self.camera_transition = None;
+            }
+            return;
+        }
+        if let Some(controller) = &mut self.camera_controller {
+            controller.update(&mut self.camera, dt);
+        }
+    }
+
+    /// Moves the camera to `view` over [`Context::CAMERA_TRANSITION`] seconds.
+    pub fn go_to_view(&mut self, view: &CameraView) {
+        self.camera_transition = Some(CameraTransition::new(
+            &mut self.camera,
+            view,
+            Self::CAMERA_TRANSITION,
+        ));
+        self.request_redraw();
+    }
+
+    pub fn bookmarks(&self) -> &CameraBookmarks {
+        &self.bookmarks
+    }
+
+    pub fn bookmarks_mut(&mut self) -> &mut CameraBookmarks {
+        &mut self.bookmarks
+    }
+
+    /// Moves the camera to the bookmark named `name`, returns `false` if
+    /// there is none.
+    pub fn recall_bookmark(&mut self, name: &str) -> bool {
+        match self.bookmarks.get(name) {
+            Some(bookmark) => {
+                let view = bookmark.view;
+                self.go_to_view(&view);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the camera to the bookmark bound to `slot`, returns `false` if
+    /// there is none.
+    pub fn recall_bookmark_slot(&mut self, slot: u8) -> bool {
+        match self.bookmarks.by_slot(slot) {
+            Some(bookmark) => {
+                let view = bookmark.view;
+                self.go_to_view(&view);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Bookmarks the current view in `slot` and saves the bookmark file.
+    pub fn store_bookmark_slot(&mut self, slot: u8) -> Result<(), Error> {
+        self.bookmarks
+            .store_slot(slot, CameraView::from_camera(&self.camera));
+        self.save_bookmarks()
+    }
+
+    /// Writes the bookmarks to [`ContextOptions::bookmarks`], does nothing
+    /// without a path. A file that failed to load is never overwritten.
+    pub fn save_bookmarks(&self) -> Result<(), Error> {
+        match &self.options.bookmarks {
+            Some(path) if self.bookmarks_unreadable => {
+                Err(Error::BookmarksUnreadable { path: path.clone() })
+            }
+            Some(path) => self.bookmarks.save(path),
+            None => Ok(()),
+        }
+    }
+
+    fn sync_interaction(&mut self) -> bool {
+        let interacting = self
+            .camera_controller
+            .as_ref()
+            .is_some_and(|controller| controller.is_interacting());
+        if interacting != self.interacting {
+            self.request_redraw();
+        }
+        self.set_interacting(interacting)
+    }
+
+    /// Wall clock time of the frame in seconds since the Unix epoch, taken
+    /// from the recording while replaying.
+    pub fn unix_time(&self) -> f64 {
+        self.unix_time
+    }
+
+    /// Keyboard and mouse state, query actions and axes by name.
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    pub fn input_mut(&mut self) -> &mut Input {
+        &mut self.input
+    }
+
+    pub fn update(&mut self, timing: &FrameTiming) {
+        let scene_state = self.global_uniform.scene_state();
+        self.time += timing.dt;
+        self.unix_time = timing.unix_time;
+        self.global_uniform.time = self.time;
+        self.global_uniform.time_delta = timing.time_delta;
+        self.global_uniform.frame = timing.frame;
+        let (width, height) = self.render_backbuffer.size();
+        self.global_uniform.resolution = [width as _, height as _];
+        self.global_uniform.quality = match self.interactive_lod {
+            Some(lod) if self.interacting => lod.quality,
+            _ => 1.,
+        };
+        self.input
+            .process_position(&mut self.global_uniform, timing.dt.min(0.1));
+
+        if self.camera.updated || scene_state != self.global_uniform.scene_state() {
+            if let Some(accumulator) = &mut self.accumulator {
+                accumulator.reset();
+            }
+            self.request_redraw();
+        }
+        if let Some(accumulator) = &self.accumulator {
+            self.global_uniform.sample_index = accumulator.samples();
+            if !accumulator.is_converged() {
+                self.request_redraw();
+            }
+        }
+        self.global_uniform.jitter = match (&self.accumulator, &self.taa) {
+            (Some(accumulator), _) if accumulator.samples() > 0 => {
+                let index = accumulator.samples();
+                [halton(2, index) - 0.5, halton(3, index) - 0.5]
+            }
+            (_, Some(_)) => Taa::jitter(self.global_uniform.frame),
+            _ => [0.; 2],
+        };
 
         self.global_uniform_binding
             .update(&self.queue, &self.global_uniform);
-
         self.camera_binding.update(&self.queue, &mut self.camera);
+        self.input_binding.update(
+            &self.queue,
+            &self.input,
+            (self.width, self.height),
+            self.render_backbuffer.size(),
+        );
     }
 
     pub fn resize(&mut self, width: u32, height: u32) {
@@ -207,15 +709,211 @@ impl Context {
         self.height = height;
         self.surface_config.height = height;
         self.surface_config.width = width;
-        self.surface.configure(&self.device, &self.surface_config);
+        self.configure_surface();
 
         self.rgb_texture = create_rgb_framebuffer(&self.device, &self.surface_config);
+        self.resize_backbuffer();
+
+        if let Some(controller) = &mut self.camera_controller {
+            controller.resize(width, height);
+        }
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Sets the backbuffer resolution relative to the window size.
+    ///
+    /// The backbuffer is recreated, so call [`Demo::resize`](crate::Demo::resize)
+    /// afterwards if the demo caches anything derived from it.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        let scale = clamp_render_scale(scale);
+        if scale != self.render_scale {
+            self.render_scale = scale;
+            self.resize_backbuffer();
+        }
+    }
+
+    /// Size of the backbuffer the demo renders into.
+    pub fn render_resolution(&self) -> (u32, u32) {
+        if let Some(resolution) = self.replayed_resolution {
+            return resolution;
+        }
+        let scale = match self.interactive_lod {
+            Some(lod) if self.interacting => self.render_scale * lod.render_scale,
+            _ => self.render_scale,
+        };
+        scaled_resolution(self.width, self.height, scale)
+    }
+
+    pub fn interactive_lod(&self) -> Option<&InteractiveLod> {
+        self.interactive_lod.as_ref()
+    }
+
+    /// Sets the quality used while the camera is dragged, `None` keeps full
+    /// quality at all times.
+    pub fn set_interactive_lod(&mut self, lod: Option<InteractiveLod>) {
+        self.interactive_lod = lod;
+        self.resize_backbuffer();
+    }
+
+    /// Whether the camera is being dragged.
+    pub fn is_interacting(&self) -> bool {
+        self.interacting
+    }
+
+    /// Switches between the interactive and the full quality.
+    ///
+    /// Returns `true` when the backbuffer was resized.
+    fn set_interacting(&mut self, interacting: bool) -> bool {
+        if interacting == self.interacting {
+            return false;
+        }
+        self.interacting = interacting;
+        // Frame times of the other quality level would skew the controller
+        if let Some(controller) = &mut self.dynamic_resolution {
+            controller.reset();
+        }
+        let old_resolution = self.render_backbuffer.size();
+        self.resize_backbuffer();
+        old_resolution != self.render_backbuffer.size()
+    }
+
+    /// Pins the backbuffer to the size of a replayed tick, returns `true`
+    /// when it was resized.
+    pub(crate) fn set_replayed_resolution(&mut self, resolution: (u32, u32)) -> bool {
+        let old_resolution = self.render_backbuffer.size();
+        self.replayed_resolution = Some(resolution);
+        self.resize_backbuffer();
+        old_resolution != self.render_backbuffer.size()
+    }
+
+    fn resize_backbuffer(&mut self) {
+        let resolution = self.render_resolution();
+        if resolution != self.render_backbuffer.size() {
+            self.render_backbuffer = HdrBackBuffer::new(&self.device, resolution);
+        }
+        if resolution != self.camera.viewport {
+            self.camera.set_aspect(resolution.0, resolution.1);
+        }
+        if let Some(accumulator) = &mut self.accumulator {
+            accumulator.resize(&self.device, resolution);
+        }
+        if let Some(taa) = &mut self.taa {
+            taa.resize(&self.device, resolution);
+        }
+    }
+
+    pub fn dynamic_resolution(&self) -> Option<&DynamicResolution> {
+        self.dynamic_resolution.as_ref()
+    }
+
+    /// Enables or disables the automatic render scale.
+    ///
+    /// Without [`wgpu::Features::TIMESTAMP_QUERY`] the CPU frame time is used.
+    pub fn set_dynamic_resolution(&mut self, controller: Option<DynamicResolution>) {
+        self.gpu_timer = match (controller, self.gpu_timer.take()) {
+            (Some(_), Some(timer)) => Some(timer),
+            (Some(_), None) => create_gpu_timer(&self.device, &self.queue, self.features),
+            (None, _) => None,
+        };
+        self.dynamic_resolution = controller;
+    }
+
+    /// GPU time spent in [`Demo::render`](crate::Demo::render) a few frames
+    /// ago, only measured while dynamic resolution is enabled.
+    pub fn gpu_frame_time(&self) -> Option<Duration> {
+        self.gpu_timer.as_ref().and_then(GpuTimer::last)
+    }
+
+    pub(crate) fn begin_frame_timing(&mut self) {
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.begin(&self.device, &self.queue);
+        }
+    }
+
+    pub(crate) fn end_frame_timing(&mut self) {
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.end(&self.device, &self.queue);
+        }
+    }
+
+    /// Feeds the latest frame time to the dynamic resolution controller.
+    ///
+    /// Returns `true` when the backbuffer was resized.
+    pub(crate) fn update_dynamic_resolution(&mut self, cpu_frame_time: Duration) -> bool {
+        let frame_time = match &mut self.gpu_timer {
+            Some(timer) => timer.poll(&self.device),
+            None => Some(cpu_frame_time),
+        };
+        let (Some(frame_time), Some(controller)) = (frame_time, &mut self.dynamic_resolution)
+        else {
+            return false;
+        };
+        if self.interacting && self.interactive_lod.is_some() {
+            return false;
+        }
+        let Some(scale) = controller.record(frame_time, self.render_scale) else {
+            return false;
+        };
+        let old_resolution = self.render_backbuffer.size();
+        self.set_render_scale(scale);
+        old_resolution != self.render_backbuffer.size()
+    }
+
+    pub fn set_present_filter(&mut self, filter: PresentFilter) {
+        self.present_settings.filter = filter as _;
+        self.present_pipeline
+            .update_settings(&self.queue, &self.present_settings);
+    }
+
+    /// Maximal number of accumulated frames, `None` when accumulation is off.
+    pub fn accumulation(&self) -> Option<u32> {
+        self.accumulator.as_ref().map(Accumulator::max_samples)
+    }
+
+    /// Enables progressive accumulation of up to `max_samples` frames,
+    /// `None` or `0` disables it.
+    ///
+    /// While the camera and the global uniform stay the same, each frame of
+    /// the backbuffer is blended into a history that gets presented instead.
+    /// `Uniform::sample_index` tells shaders which sample they render so they
+    /// can jitter, it's `0` right after a change.
+    pub fn set_accumulation(&mut self, max_samples: Option<u32>) {
+        let max_samples = max_samples.filter(|samples| *samples > 0);
+        if max_samples != self.accumulation() {
+            self.accumulator = max_samples.map(|samples| {
+                Accumulator::new(&self.device, self.render_backbuffer.size(), samples)
+            });
+            self.global_uniform.sample_index = 0;
+            self.request_redraw();
+        }
+    }
+
+    pub fn taa(&self) -> bool {
+        self.taa.is_some()
+    }
 
-        self.camera.set_aspect(width, height);
+    /// Enables temporal anti-aliasing.
+    ///
+    /// Shaders offset their rays by `Uniform::jitter` and jitter the ray
+    /// start, the resolve pass then averages frames over time. The bundled
+    /// raycast shaders jitter with interleaved gradient noise instead of a
+    /// blue noise texture, it needs no extra binding and is as even within
+    /// the 3x3 neighbourhoods the resolve pass clamps to.
+    pub fn set_taa(&mut self, taa: bool) {
+        if taa != self.taa.is_some() {
+            self.taa = taa.then(|| Taa::new(&self.device, self.render_backbuffer.size()));
+            self.request_redraw();
+        }
     }
 
-    pub fn render(&self) -> Result<(), wgpu::SurfaceError> {
-        let frame = self.surface.get_current_texture()?;
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let Some(surface) = &self.surface else {
+            return Err(wgpu::SurfaceError::Lost);
+        };
+        let frame = surface.get_current_texture()?;
         let frame_view = frame.texture.create_view(&Default::default());
 
         let mut encoder = self
@@ -224,6 +922,24 @@ impl Context {
                 label: Some("Present Encoder"),
             });
 
+        let mut backbuffer = &self.render_backbuffer;
+        if let Some(taa) = &mut self.taa {
+            let target = self.camera.build_projection_view_matrix() * self.camera.target.extend(1.);
+            taa.record(
+                &self.queue,
+                &mut encoder,
+                &self.global_uniform_binding,
+                &self.camera_binding,
+                backbuffer,
+                target.z / target.w,
+            );
+            backbuffer = taa.output();
+        }
+        if let Some(accumulator) = &mut self.accumulator {
+            accumulator.record(&mut encoder, &self.global_uniform_binding, backbuffer);
+            backbuffer = accumulator.output();
+        }
+
         let rgb = self.rgb_texture.create_view(&Default::default());
         let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Present Pass"),
@@ -251,13 +967,14 @@ impl Context {
         self.present_pipeline.record(
             &mut rpass,
             &self.global_uniform_binding,
-            &self.render_backbuffer.render_bind_group,
+            &backbuffer.render_bind_group,
         );
         drop(rpass);
 
         self.queue.submit(Some(encoder.finish()));
 
         frame.present();
+        self.camera_binding.frame_rendered();
 
         Ok(())
     }
@@ -283,6 +1000,117 @@ impl std::fmt::Display for RendererInfo {
     }
 }
 
+/// Bindings at [`ContextOptions::input_map`], the defaults without a file.
+fn load_input_map(options: &ContextOptions) -> InputMap {
+    let Some(path) = &options.input_map else {
+        return InputMap::default();
+    };
+    InputMap::load(path).unwrap_or_else(|e| {
+        eprintln!("Failed to load the input map from {}: {e}", path.display());
+        InputMap::default()
+    })
+}
+
+/// Bookmarks at [`ContextOptions::bookmarks`], a missing file is an empty set.
+/// The flag tells whether the file exists but couldn't be read.
+fn load_bookmarks(options: &ContextOptions) -> (CameraBookmarks, bool) {
+    let Some(path) = options.bookmarks.as_ref().filter(|path| path.exists()) else {
+        return (CameraBookmarks::new(), false);
+    };
+    match CameraBookmarks::load(path) {
+        Ok(bookmarks) => (bookmarks, false),
+        Err(e) => {
+            eprintln!(
+                "Failed to load camera bookmarks from {}, they won't be saved: {e}",
+                path.display()
+            );
+            (CameraBookmarks::new(), true)
+        }
+    }
+}
+
+fn clamp_render_scale(scale: f32) -> f32 {
+    if scale.is_finite() {
+        scale.clamp(0.25, 2.)
+    } else {
+        1.
+    }
+}
+
+fn scaled_resolution(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    let scale = |x: u32| ((x as f32 * scale).round() as u32).max(1);
+    (scale(width), scale(height))
+}
+
+fn create_gpu_timer(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    features: wgpu::Features,
+) -> Option<GpuTimer> {
+    features
+        .contains(wgpu::Features::TIMESTAMP_QUERY)
+        .then(|| GpuTimer::new(device, queue))
+}
+
+fn create_present_pipeline(
+    device: &wgpu::Device,
+    surface_format: wgpu::TextureFormat,
+    settings: PresentUniform,
+) -> PresentPipeline {
+    let present_shader = wgpu::include_wgsl!("../shaders/present.wgsl");
+    PresentPipeline::new(device, surface_format, present_shader, settings)
+}
+
+/// wgpu 0.18 reports a lost device as a validation error caused by
+/// `DeviceError::Lost` of wgpu-core.
+fn caused_by_device_loss(error: &(dyn std::error::Error + 'static)) -> bool {
+    std::iter::successors(Some(error), |error| error.source()).any(|error| {
+        matches!(
+            error.downcast_ref(),
+            Some(wgpu::core::device::DeviceError::Lost)
+        )
+    })
+}
+
+/// Prefers non-sRGB formats since `present.wgsl` encodes sRGB by itself.
+/// With `hdr` scRGB is tried first. 10-bit formats aren't offered for HDR,
+/// wgpu can't request the HDR10 color space so they'd only show SDR.
+fn choose_surface_format(formats: &[wgpu::TextureFormat], hdr: bool) -> wgpu::TextureFormat {
+    const PREFERRED: [wgpu::TextureFormat; 2] = [
+        wgpu::TextureFormat::Bgra8Unorm,
+        wgpu::TextureFormat::Rgba8Unorm,
+    ];
+    const PREFERRED_HDR: [wgpu::TextureFormat; 1] = [wgpu::TextureFormat::Rgba16Float];
+    let hdr_formats = if hdr { &PREFERRED_HDR[..] } else { &[] };
+    hdr_formats
+        .iter()
+        .chain(&PREFERRED)
+        .copied()
+        .find(|format| formats.contains(format))
+        .or_else(|| formats.iter().copied().find(|format| !format.is_srgb()))
+        .or_else(|| formats.first().copied())
+        .unwrap_or(wgpu::TextureFormat::Bgra8Unorm)
+}
+
+fn is_vsync(present_mode: wgpu::PresentMode) -> bool {
+    matches!(
+        present_mode,
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::Fifo | wgpu::PresentMode::FifoRelaxed
+    )
+}
+
+fn choose_present_mode(
+    supported: &[wgpu::PresentMode],
+    requested: wgpu::PresentMode,
+) -> wgpu::PresentMode {
+    match requested {
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => requested,
+        mode if supported.contains(&mode) => mode,
+        mode if is_vsync(mode) => wgpu::PresentMode::AutoVsync,
+        _ => wgpu::PresentMode::AutoNoVsync,
+    }
+}
+
 fn create_rgb_framebuffer(
     device: &wgpu::Device,
     config: &wgpu::SurfaceConfiguration,
@@ -305,3 +1133,73 @@ fn create_rgb_framebuffer(
 
     device.create_texture(multisampled_frame_descriptor)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use wgpu::{PresentMode, TextureFormat};
+
+    #[test]
+    fn surface_format_prefers_linear_8_bit() {
+        let formats = [TextureFormat::Bgra8UnormSrgb, TextureFormat::Rgba8Unorm];
+        assert_eq!(
+            choose_surface_format(&formats, false),
+            TextureFormat::Rgba8Unorm
+        );
+
+        let formats = [TextureFormat::Bgra8UnormSrgb, TextureFormat::Rgb10a2Unorm];
+        assert_eq!(
+            choose_surface_format(&formats, false),
+            TextureFormat::Rgb10a2Unorm
+        );
+
+        let formats = [TextureFormat::Bgra8UnormSrgb];
+        assert_eq!(
+            choose_surface_format(&formats, false),
+            TextureFormat::Bgra8UnormSrgb
+        );
+        assert_eq!(choose_surface_format(&[], false), TextureFormat::Bgra8Unorm);
+    }
+
+    #[test]
+    fn hdr_surface_format_only_when_requested() {
+        let formats = [TextureFormat::Rgba8Unorm, TextureFormat::Rgba16Float];
+        assert_eq!(
+            choose_surface_format(&formats, true),
+            TextureFormat::Rgba16Float
+        );
+        assert_eq!(
+            choose_surface_format(&formats, false),
+            TextureFormat::Rgba8Unorm
+        );
+
+        // 10 bit surfaces have no wider range than 8 bit ones
+        let formats = [TextureFormat::Rgb10a2Unorm, TextureFormat::Bgra8Unorm];
+        assert_eq!(
+            choose_surface_format(&formats, true),
+            TextureFormat::Bgra8Unorm
+        );
+    }
+
+    #[test]
+    fn present_mode_falls_back_to_auto() {
+        let supported = [PresentMode::Fifo, PresentMode::Immediate];
+        assert_eq!(
+            choose_present_mode(&supported, PresentMode::Immediate),
+            PresentMode::Immediate
+        );
+        assert_eq!(
+            choose_present_mode(&supported, PresentMode::FifoRelaxed),
+            PresentMode::AutoVsync
+        );
+        assert_eq!(
+            choose_present_mode(&supported, PresentMode::Mailbox),
+            PresentMode::AutoNoVsync
+        );
+        assert_eq!(
+            choose_present_mode(&[], PresentMode::AutoVsync),
+            PresentMode::AutoVsync
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{GlobalUniformBinding, HdrBackBuffer, Uniform};
+
+/// Averages consecutive frames of the backbuffer into a history buffer
+/// while the view doesn't change.
+///
+/// Shaders see the number of frames already accumulated in
+/// `Uniform::sample_index` and jitter their samples when it isn't zero.
+pub(crate) struct Accumulator {
+    pipeline: wgpu::RenderPipeline,
+    history: [HdrBackBuffer; 2],
+    current: usize,
+    samples: u32,
+    max_samples: u32,
+}
+
+impl Accumulator {
+    pub(crate) fn new(device: &wgpu::Device, resolution: (u32, u32), max_samples: u32) -> Self {
+        let shader =
+            device.create_shader_module(wgpu::include_wgsl!("../../shaders/accumulate.wgsl"));
+        let global_bind_group_layout = device.create_bind_group_layout(&Uniform::DESC);
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&HdrBackBuffer::DESC_RENDER);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Accumulate Pass Layout"),
+            bind_group_layouts: &[
+                &global_bind_group_layout,
+                &texture_bind_group_layout,
+                &texture_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Accumulate Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HdrBackBuffer::FORMAT,
+                    blend: None,
+                    write_mask: Default::default(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            history: create_history(device, resolution),
+            current: 0,
+            samples: 0,
+            max_samples: max_samples.max(1),
+        }
+    }
+
+    /// Number of frames in the history, the next one gets this sample index.
+    pub(crate) fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    pub(crate) fn max_samples(&self) -> u32 {
+        self.max_samples
+    }
+
+    pub(crate) fn is_converged(&self) -> bool {
+        self.samples >= self.max_samples
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.samples = 0;
+    }
+
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, resolution: (u32, u32)) {
+        if resolution != self.history[0].size() {
+            self.history = create_history(device, resolution);
+            self.reset();
+        }
+    }
+
+    /// Blends `backbuffer` into the history, does nothing once converged.
+    pub(crate) fn record(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        uniform: &GlobalUniformBinding,
+        backbuffer: &HdrBackBuffer,
+    ) {
+        if self.is_converged() {
+            return;
+        }
+        let next = 1 - self.current;
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Accumulate Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.history[next].texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &uniform.binding, &[]);
+        rpass.set_bind_group(1, &backbuffer.render_bind_group, &[]);
+        rpass.set_bind_group(2, &self.history[self.current].render_bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+        drop(rpass);
+
+        self.current = next;
+        self.samples += 1;
+    }
+
+    /// History holding the average of every accumulated frame.
+    pub(crate) fn output(&self) -> &HdrBackBuffer {
+        &self.history[self.current]
+    }
+}
+
+fn create_history(device: &wgpu::Device, resolution: (u32, u32)) -> [HdrBackBuffer; 2] {
+    [
+        HdrBackBuffer::new(device, resolution),
+        HdrBackBuffer::new(device, resolution),
+    ]
+}
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+/// Adjusts the render scale to keep the frame time near a target.
+///
+/// The cost of a frame is assumed to grow with the pixel count, so the scale
+/// moves by the square root of the ratio between the target and the
+/// measured time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DynamicResolution {
+    pub target_frame_time: Duration,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    /// Number of measurements averaged before the scale changes.
+    pub interval: u32,
+    /// Relative change below which the scale is left alone.
+    pub tolerance: f32,
+
+    average: f32,
+    samples: u32,
+}
+
+impl DynamicResolution {
+    pub fn new(target_frame_time: Duration, min_scale: f32, max_scale: f32) -> Self {
+        Self {
+            target_frame_time,
+            min_scale: min_scale.min(max_scale),
+            max_scale: max_scale.max(min_scale),
+            interval: 15,
+            tolerance: 0.05,
+
+            average: 0.,
+            samples: 0,
+        }
+    }
+
+    /// Target frame rate between half and full resolution.
+    pub fn with_fps(fps: f32) -> Self {
+        Self::new(Duration::from_secs_f32(1. / fps), 0.5, 1.)
+    }
+
+    /// Feeds a frame time measured at `scale`, returns the new scale once
+    /// enough samples were gathered and the change is significant.
+    pub fn record(&mut self, frame_time: Duration, scale: f32) -> Option<f32> {
+        self.average += frame_time.as_secs_f32();
+        self.samples += 1;
+        if self.samples < self.interval.max(1) {
+            return None;
+        }
+
+        let average = self.average / self.samples as f32;
+        self.average = 0.;
+        self.samples = 0;
+        if average <= 0. {
+            return None;
+        }
+
+        let ratio = self.target_frame_time.as_secs_f32() / average;
+        let new_scale = (scale * ratio.sqrt()).clamp(self.min_scale, self.max_scale);
+        ((new_scale - scale).abs() > scale * self.tolerance).then_some(new_scale)
+    }
+
+    /// Drops gathered samples, e.g. after the scene changed.
+    pub fn reset(&mut self) {
+        self.average = 0.;
+        self.samples = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: Duration = Duration::from_millis(10);
+
+    fn gather(resolution: &mut DynamicResolution, frame_time: Duration, scale: f32) -> Option<f32> {
+        (0..resolution.interval)
+            .map(|_| resolution.record(frame_time, scale))
+            .last()
+            .flatten()
+    }
+
+    #[test]
+    fn waits_for_a_full_interval() {
+        let mut resolution = DynamicResolution::new(TARGET, 0.25, 1.);
+        for _ in 1..resolution.interval {
+            assert_eq!(resolution.record(TARGET * 4, 1.), None);
+        }
+        assert_eq!(resolution.record(TARGET * 4, 1.), Some(0.5));
+    }
+
+    #[test]
+    fn scales_with_the_square_root_within_bounds() {
+        let mut resolution = DynamicResolution::new(TARGET, 0.5, 1.);
+        assert_eq!(gather(&mut resolution, TARGET / 4, 0.5), Some(1.));
+        assert_eq!(gather(&mut resolution, TARGET * 16, 1.), Some(0.5));
+    }
+
+    #[test]
+    fn ignores_small_changes() {
+        let mut resolution = DynamicResolution::new(TARGET, 0.5, 1.);
+        assert_eq!(gather(&mut resolution, TARGET, 0.75), None);
+        assert_eq!(gather(&mut resolution, TARGET.mul_f32(1.02), 0.75), None);
+    }
+
+    #[test]
+    fn reset_drops_samples() {
+        let mut resolution = DynamicResolution::new(TARGET, 0.25, 1.);
+        for _ in 1..resolution.interval {
+            resolution.record(TARGET * 4, 1.);
+        }
+        resolution.reset();
+        assert_eq!(resolution.record(TARGET * 4, 1.), None);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::HdrBackBuffer;
+
+/// Pair of textures swapped every frame, so passes can read what was written
+/// the frame before: accumulation, trails, simulations or temporal filters.
+///
+/// The previous frame is read through [`FeedbackTexture::read_bind_group`],
+/// a texture at binding 0 and its sampler at binding 1. The current frame is
+/// written through [`FeedbackTexture::write_bind_group`], a write-only
+/// storage texture at binding 0, or for 2D textures by rendering to
+/// [`FeedbackTexture::write_view`]. Call [`FeedbackTexture::swap`] once the
+/// frame is written, afterwards the read side holds the result.
+///
+/// Layouts for the default format are in the `DESC_*` constants, other
+/// formats use [`FeedbackTexture::read_layout`] and
+/// [`FeedbackTexture::write_layout`].
+pub struct FeedbackTexture {
+    textures: [wgpu::Texture; 2],
+    views: [wgpu::TextureView; 2],
+    read_bind_groups: [wgpu::BindGroup; 2],
+    write_bind_groups: [wgpu::BindGroup; 2],
+    read_layout: wgpu::BindGroupLayout,
+    write_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    /// Texture written this frame, the other one is read.
+    current: usize,
+}
+
+impl FeedbackTexture {
+    pub const FORMAT: wgpu::TextureFormat = HdrBackBuffer::FORMAT;
+
+    pub const DESC_READ_2D: wgpu::BindGroupLayoutDescriptor<'static> =
+        wgpu::BindGroupLayoutDescriptor {
+            label: Some("Feedback Texture: Read 2D Bind Group Layout"),
+            entries: &read_entries(
+                wgpu::TextureViewDimension::D2,
+                wgpu::TextureSampleType::Float { filterable: true },
+            ),
+        };
+    pub const DESC_READ_3D: wgpu::BindGroupLayoutDescriptor<'static> =
+        wgpu::BindGroupLayoutDescriptor {
+            label: Some("Feedback Texture: Read 3D Bind Group Layout"),
+            entries: &read_entries(
+                wgpu::TextureViewDimension::D3,
+                wgpu::TextureSampleType::Float { filterable: true },
+            ),
+        };
+    pub const DESC_WRITE_2D: wgpu::BindGroupLayoutDescriptor<'static> =
+        wgpu::BindGroupLayoutDescriptor {
+            label: Some("Feedback Texture: Write 2D Bind Group Layout"),
+            entries: &[write_entry(wgpu::TextureViewDimension::D2, Self::FORMAT)],
+        };
+    pub const DESC_WRITE_3D: wgpu::BindGroupLayoutDescriptor<'static> =
+        wgpu::BindGroupLayoutDescriptor {
+            label: Some("Feedback Texture: Write 3D Bind Group Layout"),
+            entries: &[write_entry(wgpu::TextureViewDimension::D3, Self::FORMAT)],
+        };
+
+    pub fn new_2d(device: &wgpu::Device, (width, height): (u32, u32)) -> Self {
+        Self::new(
+            device,
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            wgpu::TextureDimension::D2,
+            Self::FORMAT,
+        )
+    }
+
+    pub fn new_3d(device: &wgpu::Device, (width, height, depth): (u32, u32, u32)) -> Self {
+        Self::new(
+            device,
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: depth,
+            },
+            wgpu::TextureDimension::D3,
+            Self::FORMAT,
+        )
+    }
+
+    /// Both textures start zeroed. `format` has to support storage binding,
+    /// formats that can't be filtered get a non-filtering sampler.
+    pub fn new(
+        device: &wgpu::Device,
+        size: wgpu::Extent3d,
+        dimension: wgpu::TextureDimension,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let view_dimension = match dimension {
+            wgpu::TextureDimension::D3 => wgpu::TextureViewDimension::D3,
+            _ => wgpu::TextureViewDimension::D2,
+        };
+        let sample_type = format
+            .sample_type(None)
+            .unwrap_or(wgpu::TextureSampleType::Float { filterable: false });
+        let filterable = matches!(
+            sample_type,
+            wgpu::TextureSampleType::Float { filterable: true }
+        );
+
+        let read_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Feedback Texture: Read Bind Group Layout"),
+            entries: &read_entries(view_dimension, sample_type),
+        });
+        let write_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Feedback Texture: Write Bind Group Layout"),
+            entries: &[write_entry(view_dimension, format)],
+        });
+        let filter = if filterable {
+            wgpu::FilterMode::Linear
+        } else {
+            wgpu::FilterMode::Nearest
+        };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Feedback Texture Sampler"),
+            mag_filter: filter,
+            min_filter: filter,
+            ..Default::default()
+        });
+
+        // Only 2D textures can be render attachments
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::STORAGE_BINDING
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST;
+        if dimension == wgpu::TextureDimension::D2 {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+        let create_texture = || {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Texture: Feedback"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let textures = [create_texture(), create_texture()];
+        let views = [
+            textures[0].create_view(&Default::default()),
+            textures[1].create_view(&Default::default()),
+        ];
+
+        let read_bind_group = |view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Feedback Texture: Read Bind Group"),
+                layout: &read_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            })
+        };
+        let write_bind_group = |view: &wgpu::TextureView| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Feedback Texture: Write Bind Group"),
+                layout: &write_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                }],
+            })
+        };
+        let read_bind_groups = [read_bind_group(&views[0]), read_bind_group(&views[1])];
+        let write_bind_groups = [write_bind_group(&views[0]), write_bind_group(&views[1])];
+
+        Self {
+            textures,
+            views,
+            read_bind_groups,
+            write_bind_groups,
+            read_layout,
+            write_layout,
+            sampler,
+            current: 0,
+        }
+    }
+
+    /// Recreates both textures if `size` changed, clearing their contents.
+    pub fn resize(&mut self, device: &wgpu::Device, size: wgpu::Extent3d) {
+        if size != self.size() {
+            *self = Self::new(device, size, self.dimension(), self.format());
+        }
+    }
+
+    /// Makes the texture written this frame the one read next frame.
+    pub fn swap(&mut self) {
+        self.current = 1 - self.current;
+    }
+
+    /// Texture written during the previous frame, or this frame after
+    /// [`FeedbackTexture::swap`].
+    pub fn read_bind_group(&self) -> &wgpu::BindGroup {
+        &self.read_bind_groups[1 - self.current]
+    }
+
+    pub fn write_bind_group(&self) -> &wgpu::BindGroup {
+        &self.write_bind_groups[self.current]
+    }
+
+    pub fn read_view(&self) -> &wgpu::TextureView {
+        &self.views[1 - self.current]
+    }
+
+    /// Render target of the current frame, only 2D textures can be rendered to.
+    pub fn write_view(&self) -> &wgpu::TextureView {
+        &self.views[self.current]
+    }
+
+    pub fn read_texture(&self) -> &wgpu::Texture {
+        &self.textures[1 - self.current]
+    }
+
+    pub fn write_texture(&self) -> &wgpu::Texture {
+        &self.textures[self.current]
+    }
+
+    pub fn read_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.read_layout
+    }
+
+    pub fn write_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.write_layout
+    }
+
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    pub fn size(&self) -> wgpu::Extent3d {
+        self.textures[0].size()
+    }
+
+    pub fn dimension(&self) -> wgpu::TextureDimension {
+        self.textures[0].dimension()
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.textures[0].format()
+    }
+}
+
+const fn read_entries(
+    view_dimension: wgpu::TextureViewDimension,
+    sample_type: wgpu::TextureSampleType,
+) -> [wgpu::BindGroupLayoutEntry; 2] {
+    let sampler = match sample_type {
+        wgpu::TextureSampleType::Float { filterable: true } => wgpu::SamplerBindingType::Filtering,
+        _ => wgpu::SamplerBindingType::NonFiltering,
+    };
+    [
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE),
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension,
+                multisampled: false,
+            },
+            count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding: 1,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE),
+            ty: wgpu::BindingType::Sampler(sampler),
+            count: None,
+        },
+    ]
+}
+
+const fn write_entry(
+    view_dimension: wgpu::TextureViewDimension,
+    format: wgpu::TextureFormat,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::FRAGMENT.union(wgpu::ShaderStages::COMPUTE),
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format,
+            view_dimension,
+        },
+        count: None,
+    }
+}
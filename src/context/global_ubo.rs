@@ -60,7 +60,16 @@ pub struct Uniform {
     pub mouse_pressed: u32,
     pub time: f32,
     pub time_delta: f32,
+    /// Frames accumulated since the view last changed, see
+    /// [`ContextOptions::accumulation`](crate::ContextOptions::accumulation).
+    pub sample_index: u32,
+    /// Level of detail in `(0, 1]`, lowered while the camera is dragged,
+    /// see [`InteractiveLod`](crate::InteractiveLod).
+    pub quality: f32,
     _padding: f32,
+    /// Sub-pixel offset in pixels of the camera rays, non-zero while
+    /// accumulation or TAA is active.
+    pub jitter: [f32; 2],
 }
 
 impl Default for Uniform {
@@ -73,7 +82,10 @@ impl Default for Uniform {
             mouse_pressed: false as _,
             frame: 0,
             time_delta: 1. / 60.,
+            sample_index: 0,
+            quality: 1.,
             _padding: 0.,
+            jitter: [0.; 2],
         }
     }
 }
@@ -111,11 +123,37 @@ impl Uniform {
             time,
             time_delta,
             frame,
+            sample_index: 0,
+            quality: 1.,
             _padding: 0.,
+            jitter: [0.; 2],
         }
     }
 }
 
+impl Uniform {
+    /// Fields that change the rendered image. Time is left out and the
+    /// cursor only counts while a button is held.
+    pub(crate) fn scene_state(&self) -> [f32; 9] {
+        let mouse = if self.mouse_pressed != 0 {
+            self.mouse
+        } else {
+            [0.; 2]
+        };
+        [
+            self.pos[0],
+            self.pos[1],
+            self.pos[2],
+            self.resolution[0],
+            self.resolution[1],
+            mouse[0],
+            mouse[1],
+            self.mouse_pressed as f32,
+            self.quality,
+        ]
+    }
+}
+
 impl std::fmt::Display for Uniform {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let time = Duration::from_secs_f32(self.time);
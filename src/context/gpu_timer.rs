@@ -0,0 +1,142 @@
+use crate::NonZeroSized;
+
+use std::{
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+const FREE: u8 = 0;
+const PENDING: u8 = 1;
+const MAPPED: u8 = 2;
+
+/// Number of frames that can be in flight before timing skips a frame.
+const FRAMES_IN_FLIGHT: usize = 3;
+const TIMESTAMP_SIZE: u64 = u64::SIZE.get();
+
+struct Readback {
+    buffer: wgpu::Buffer,
+    state: Arc<AtomicU8>,
+}
+
+/// Measures the GPU time of everything submitted between [`GpuTimer::begin`]
+/// and [`GpuTimer::end`] with timestamp queries.
+///
+/// Results are read back asynchronously a few frames later, so the timer
+/// never stalls the pipeline.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    readbacks: Vec<Readback>,
+    period: f32,
+    current: usize,
+    active: bool,
+    last: Option<Duration>,
+}
+
+impl GpuTimer {
+    /// Requires [`wgpu::Features::TIMESTAMP_QUERY`].
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Gpu Timer Queries"),
+            count: 2 * FRAMES_IN_FLIGHT as u32,
+            ty: wgpu::QueryType::Timestamp,
+        });
+        let readbacks = (0..FRAMES_IN_FLIGHT)
+            .map(|_| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Gpu Timer Buffer"),
+                    size: 2 * TIMESTAMP_SIZE,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE
+                        | wgpu::BufferUsages::COPY_DST
+                        | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                state: Arc::new(AtomicU8::new(FREE)),
+            })
+            .collect();
+
+        Self {
+            query_set,
+            readbacks,
+            period: queue.get_timestamp_period(),
+            current: 0,
+            active: false,
+            last: None,
+        }
+    }
+
+    /// Starts timing, skipped when every readback buffer is still in flight.
+    pub fn begin(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        self.active = self.readbacks[self.current].state.load(Ordering::Acquire) == FREE;
+        if !self.active {
+            return;
+        }
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Gpu Timer Begin"),
+        });
+        encoder.write_timestamp(&self.query_set, 2 * self.current as u32);
+        queue.submit(Some(encoder.finish()));
+    }
+
+    pub fn end(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if !self.active {
+            return;
+        }
+        self.active = false;
+
+        let first = 2 * self.current as u32;
+        let readback = &self.readbacks[self.current];
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Gpu Timer End"),
+        });
+        encoder.write_timestamp(&self.query_set, first + 1);
+        encoder.resolve_query_set(&self.query_set, first..first + 2, &readback.buffer, 0);
+        queue.submit(Some(encoder.finish()));
+
+        readback.state.store(PENDING, Ordering::Release);
+        let state = Arc::clone(&readback.state);
+        readback
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |res| {
+                state.store(if res.is_ok() { MAPPED } else { FREE }, Ordering::Release);
+            });
+
+        self.current = (self.current + 1) % FRAMES_IN_FLIGHT;
+    }
+
+    /// Collects finished measurements, returns the newest one if any.
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<Duration> {
+        device.poll(wgpu::Maintain::Poll);
+
+        let mut newest = None;
+        for offset in 1..=FRAMES_IN_FLIGHT {
+            // Oldest submission first
+            let readback = &self.readbacks[(self.current + offset) % FRAMES_IN_FLIGHT];
+            if readback.state.load(Ordering::Acquire) != MAPPED {
+                continue;
+            }
+            {
+                let view = readback.buffer.slice(..).get_mapped_range();
+                let timestamps: &[u64] = bytemuck::cast_slice(&view);
+                let ticks = timestamps[1].saturating_sub(timestamps[0]);
+                newest = Some(Duration::from_nanos(
+                    (ticks as f64 * self.period as f64) as u64,
+                ));
+            }
+            readback.buffer.unmap();
+            readback.state.store(FREE, Ordering::Release);
+        }
+        if newest.is_some() {
+            self.last = newest;
+        }
+        newest
+    }
+
+    /// Latest finished measurement.
+    pub fn last(&self) -> Option<Duration> {
+        self.last
+    }
+}
@@ -1,5 +1,6 @@
 pub struct HdrBackBuffer {
     pub texture_view: wgpu::TextureView,
+    size: (u32, u32),
     pub render_bind_group: wgpu::BindGroup,
     pub storage_bind_group: wgpu::BindGroup,
 }
@@ -82,11 +83,16 @@ impl HdrBackBuffer {
 
         Self {
             texture_view,
+            size: (width, height),
             render_bind_group,
             storage_bind_group,
         }
     }
 
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
     pub fn format(&self) -> wgpu::TextureFormat {
         Self::FORMAT
     }
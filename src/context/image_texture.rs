@@ -0,0 +1,428 @@
+use crate::Error;
+
+use half::f16;
+use image::{ColorType, DynamicImage};
+
+use std::path::Path;
+
+/// How an [`ImageTexture`] is uploaded and sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureOptions {
+    /// Whether 8 and 16 bit images hold sRGB encoded colors, turn it off for
+    /// data like noise, normal maps or LUTs. HDR images are always linear.
+    pub srgb: bool,
+    pub mipmaps: bool,
+    /// Store the bottom row first, like Shadertoy does.
+    pub flip_y: bool,
+    pub filter: wgpu::FilterMode,
+    pub address_mode: wgpu::AddressMode,
+    /// Anisotropic filtering up to 16 samples, only used with mipmaps and
+    /// linear filtering.
+    pub anisotropy: u16,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            srgb: true,
+            mipmaps: true,
+            flip_y: false,
+            filter: wgpu::FilterMode::Linear,
+            address_mode: wgpu::AddressMode::Repeat,
+            anisotropy: 1,
+        }
+    }
+}
+
+impl TextureOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn address_mode(mut self, address_mode: wgpu::AddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    pub fn anisotropy(mut self, anisotropy: u16) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+}
+
+/// Sampled 2D texture loaded from a PNG, JPEG or Radiance HDR image, e.g.
+/// noise, matcaps or color grading LUTs.
+///
+/// 8 bit images become `Rgba8UnormSrgb` or `Rgba8Unorm` textures, wider and
+/// HDR ones `Rgba16Float`. Shaders see the texture at binding 0 and its
+/// sampler at binding 1 of [`ImageTexture::DESC`].
+pub struct ImageTexture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl ImageTexture {
+    pub const DESC: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+        label: Some("Image Texture Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE),
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE),
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    };
+
+    /// Decodes the image at `path`, the format is guessed from the extension.
+    pub fn load(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: impl AsRef<Path>,
+        options: TextureOptions,
+    ) -> Result<Self, Error> {
+        let image = image::open(path)?;
+        Self::from_image(device, queue, &image, options)
+    }
+
+    /// Decodes an encoded image, e.g. from `include_bytes!`.
+    pub fn from_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        options: TextureOptions,
+    ) -> Result<Self, Error> {
+        let image = image::load_from_memory(bytes)?;
+        Self::from_image(device, queue, &image, options)
+    }
+
+    /// Fails on images without texels.
+    pub fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &DynamicImage,
+        options: TextureOptions,
+    ) -> Result<Self, Error> {
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 {
+            return Err(Error::EmptyImage { width, height });
+        }
+        let flipped;
+        let image = if options.flip_y {
+            flipped = image.flipv();
+            &flipped
+        } else {
+            image
+        };
+        let (format, texel_size, data) = texel_data(image, options.srgb);
+
+        let mip_level_count = if options.mipmaps {
+            (u32::BITS - width.max(height).leading_zeros()).max(1)
+        } else {
+            1
+        };
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if mip_level_count > 1 {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Image Texture"),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            &data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * texel_size),
+                rows_per_image: None,
+            },
+            size,
+        );
+        if mip_level_count > 1 {
+            generate_mipmaps(device, queue, &texture);
+        }
+        let view = texture.create_view(&Default::default());
+
+        let mipmapped = mip_level_count > 1;
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Image Texture Sampler"),
+            address_mode_u: options.address_mode,
+            address_mode_v: options.address_mode,
+            address_mode_w: options.address_mode,
+            mag_filter: options.filter,
+            min_filter: options.filter,
+            mipmap_filter: if mipmapped {
+                options.filter
+            } else {
+                wgpu::FilterMode::Nearest
+            },
+            // Anisotropy is only valid when every filter is linear
+            anisotropy_clamp: if mipmapped && options.filter == wgpu::FilterMode::Linear {
+                options.anisotropy.clamp(1, 16)
+            } else {
+                1
+            },
+            ..Default::default()
+        });
+
+        let layout = device.create_bind_group_layout(&Self::DESC);
+        let bind_group = create_bind_group(device, &layout, &view, &sampler);
+
+        Ok(Self {
+            texture,
+            view,
+            sampler,
+            bind_group,
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+}
+
+/// Texture format, bytes per texel and the texels of the first mip level.
+fn texel_data(image: &DynamicImage, srgb: bool) -> (wgpu::TextureFormat, u32, Vec<u8>) {
+    match image.color() {
+        ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8 => {
+            let format = if srgb {
+                wgpu::TextureFormat::Rgba8UnormSrgb
+            } else {
+                wgpu::TextureFormat::Rgba8Unorm
+            };
+            (format, 4, image.to_rgba8().into_raw())
+        }
+        color => {
+            // Half floats keep the precision of 16 bit and HDR images
+            let decode = srgb && !matches!(color, ColorType::Rgb32F | ColorType::Rgba32F);
+            let channel = |value: f32| {
+                if decode {
+                    srgb_to_linear(value)
+                } else {
+                    value
+                }
+            };
+            let texels: Vec<u16> = image
+                .to_rgba32f()
+                .pixels()
+                .flat_map(|pixel| {
+                    let [r, g, b, a] = pixel.0;
+                    [channel(r), channel(g), channel(b), a]
+                })
+                .map(|value| f16::from_f32(value).to_bits())
+                .collect();
+            (
+                wgpu::TextureFormat::Rgba16Float,
+                8,
+                bytemuck::cast_slice(&texels).to_vec(),
+            )
+        }
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    view: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Image Texture Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
+
+/// Fills every mip level below the first by downsampling the one above.
+///
+/// Texels are loaded and averaged in the shader, which weights the extra
+/// row or column of odd sizes. Rendering through views of the texture
+/// format filters sRGB textures in linear space.
+fn generate_mipmaps(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+    let shader = device.create_shader_module(wgpu::include_wgsl!("../../shaders/blit.wgsl"));
+    let texture_layout = device.create_bind_group_layout(&ImageTexture::DESC);
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Mipmap Pass Layout"),
+        bind_group_layouts: &[&texture_layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Mipmap Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(texture.format().into())],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+    // Unused by the shader, the layout has a slot for it
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("Mipmap Sampler"),
+        ..Default::default()
+    });
+
+    let views: Vec<_> = (0..texture.mip_level_count())
+        .map(|level| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Mip Level View"),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Mipmap Encoder"),
+    });
+    for levels in views.windows(2) {
+        let bind_group = create_bind_group(device, &texture_layout, &levels[0], &sampler);
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Mipmap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &levels[1],
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        });
+        rpass.set_pipeline(&pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+    queue.submit(Some(encoder.finish()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::{ImageBuffer, Luma, Rgb, Rgba};
+
+    #[test]
+    fn eight_bit_images_upload_as_rgba8() {
+        let image = DynamicImage::ImageLuma8(ImageBuffer::from_pixel(2, 1, Luma([200])));
+        let (format, texel_size, data) = texel_data(&image, true);
+        assert_eq!(format, wgpu::TextureFormat::Rgba8UnormSrgb);
+        assert_eq!(texel_size, 4);
+        assert_eq!(data, [200, 200, 200, 255, 200, 200, 200, 255]);
+
+        let (format, ..) = texel_data(&image, false);
+        assert_eq!(format, wgpu::TextureFormat::Rgba8Unorm);
+    }
+
+    #[test]
+    fn wide_images_upload_as_half_floats() {
+        let image = DynamicImage::ImageRgba16(ImageBuffer::from_pixel(1, 1, Rgba([u16::MAX; 4])));
+        let (format, texel_size, data) = texel_data(&image, true);
+        assert_eq!(format, wgpu::TextureFormat::Rgba16Float);
+        assert_eq!(texel_size, 8);
+        let one = f16::ONE.to_bits().to_ne_bytes();
+        assert_eq!(data, one.repeat(4));
+    }
+
+    #[test]
+    fn srgb_decodes_16_bit_but_not_hdr_images() {
+        let half = |data: &[u8]| f16::from_ne_bytes([data[0], data[1]]).to_f32();
+
+        let image = DynamicImage::ImageRgb16(ImageBuffer::from_pixel(1, 1, Rgb([u16::MAX / 2; 3])));
+        let (_, _, data) = texel_data(&image, true);
+        assert!((half(&data) - srgb_to_linear(0.5)).abs() < 1e-3);
+
+        let image = DynamicImage::ImageRgb32F(ImageBuffer::from_pixel(1, 1, Rgb([0.5; 3])));
+        let (format, _, data) = texel_data(&image, true);
+        assert_eq!(format, wgpu::TextureFormat::Rgba16Float);
+        assert_eq!(half(&data), 0.5);
+    }
+
+    #[test]
+    fn blit_shader_validates() {
+        use wgpu::naga;
+
+        let module = naga::front::wgsl::parse_str(include_str!("../../shaders/blit.wgsl"))
+            .expect("blit.wgsl should parse");
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .expect("blit.wgsl should validate");
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{Input, NonZeroSized};
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+/// Mouse and modifier state as seen by shaders, positions are in pixels of
+/// the render backbuffer from the bottom left corner like Shadertoy's `iMouse`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct InputUniform {
+    pub cursor: [f32; 2],
+    /// Where the left button last went down.
+    pub click: [f32; 2],
+    /// Lines scrolled since the last frame.
+    pub wheel: [f32; 2],
+    /// Bit 0 left, 1 right, 2 middle.
+    pub buttons: u32,
+    /// Bit 0 shift, 1 control, 2 alt, 3 super.
+    pub modifiers: u32,
+}
+
+/// Keyboard texture and [`InputUniform`] for shaders reacting to arbitrary
+/// keys, in the layout of Shadertoy's keyboard input.
+///
+/// The keyboard is a 256x3 `R8Unorm` texture indexed by JavaScript key code
+/// (`65` is `A`, `37` the left arrow). Row 0 holds the keys down, row 1 the
+/// keys pressed this frame and row 2 flips on every press:
+///
+/// ```wgsl
+/// struct InputState {
+///     cursor: vec2<f32>,
+///     click: vec2<f32>,
+///     wheel: vec2<f32>,
+///     buttons: u32,
+///     modifiers: u32,
+/// }
+/// @group(N) @binding(0) var keyboard: texture_2d<f32>;
+/// @group(N) @binding(1) var<uniform> input: InputState;
+///
+/// let space_down = textureLoad(keyboard, vec2(32, 0), 0).r > 0.5;
+/// ```
+pub struct InputBinding {
+    pub bind_group: wgpu::BindGroup,
+    keyboard: wgpu::Texture,
+    buffer: wgpu::Buffer,
+    keys: [u8; Self::KEYBOARD_SIZE],
+    uniform: InputUniform,
+}
+
+impl InputBinding {
+    const KEYBOARD_WIDTH: u32 = 256;
+    const KEYBOARD_SIZE: usize = Self::KEYBOARD_WIDTH as usize * 3;
+
+    pub const DESC: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+        label: Some("Input Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE),
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT.union(wgpu::ShaderStages::COMPUTE),
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: Some(InputUniform::SIZE),
+                },
+                count: None,
+            },
+        ],
+    };
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let keyboard = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Keyboard Texture"),
+            size: wgpu::Extent3d {
+                width: Self::KEYBOARD_WIDTH,
+                height: 3,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Input Buffer"),
+            contents: bytemuck::bytes_of(&InputUniform::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let layout = device.create_bind_group_layout(&Self::DESC);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Input Bind Group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(
+                        &keyboard.create_view(&Default::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Textures start zeroed, nothing held
+        Self {
+            bind_group,
+            keyboard,
+            buffer,
+            keys: [0; Self::KEYBOARD_SIZE],
+            uniform: InputUniform::zeroed(),
+        }
+    }
+
+    /// Uploads the state of `input`, scaling positions from the window to
+    /// the render resolution.
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        input: &Input,
+        window_size: (u32, u32),
+        render_size: (u32, u32),
+    ) {
+        let mut keys = [0; Self::KEYBOARD_SIZE];
+        let mut set_row = |row: usize, codes: &mut dyn Iterator<Item = KeyCode>| {
+            for index in codes.filter_map(js_key_code) {
+                keys[row * Self::KEYBOARD_WIDTH as usize + index as usize] = 255;
+            }
+        };
+        set_row(0, &mut input.held_keys());
+        set_row(1, &mut input.pressed_keys());
+        set_row(2, &mut input.toggled_keys());
+        if keys != self.keys {
+            queue.write_texture(
+                self.keyboard.as_image_copy(),
+                &keys,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(Self::KEYBOARD_WIDTH),
+                    rows_per_image: None,
+                },
+                self.keyboard.size(),
+            );
+            self.keys = keys;
+        }
+
+        let scale = [
+            render_size.0 as f32 / window_size.0.max(1) as f32,
+            render_size.1 as f32 / window_size.1.max(1) as f32,
+        ];
+        let to_render = |[x, y]: [f32; 2]| [x * scale[0], render_size.1 as f32 - y * scale[1]];
+        let modifiers = input.modifiers();
+        let uniform = InputUniform {
+            cursor: to_render(input.cursor_position()),
+            click: to_render(input.click_position()),
+            wheel: input.wheel_delta(),
+            buttons: input.is_mouse_held(MouseButton::Left) as u32
+                | ((input.is_mouse_held(MouseButton::Right) as u32) << 1)
+                | ((input.is_mouse_held(MouseButton::Middle) as u32) << 2),
+            modifiers: modifiers.shift_key() as u32
+                | ((modifiers.control_key() as u32) << 1)
+                | ((modifiers.alt_key() as u32) << 2)
+                | ((modifiers.super_key() as u32) << 3),
+        };
+        if uniform != self.uniform {
+            queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+            self.uniform = uniform;
+        }
+    }
+
+    /// State uploaded by the last [`InputBinding::update`].
+    pub fn uniform(&self) -> &InputUniform {
+        &self.uniform
+    }
+
+    /// New view of the keyboard texture, to bind it next to other textures.
+    pub fn keyboard_view(&self) -> wgpu::TextureView {
+        self.keyboard.create_view(&Default::default())
+    }
+}
+
+/// JavaScript `keyCode` of `code`, the column of the keyboard texture.
+pub fn js_key_code(code: KeyCode) -> Option<u8> {
+    use KeyCode::*;
+
+    Some(match code {
+        Backspace => 8,
+        Tab => 9,
+        Enter | NumpadEnter => 13,
+        ShiftLeft | ShiftRight => 16,
+        ControlLeft | ControlRight => 17,
+        AltLeft | AltRight => 18,
+        Pause => 19,
+        CapsLock => 20,
+        Escape => 27,
+        Space => 32,
+        PageUp => 33,
+        PageDown => 34,
+        End => 35,
+        Home => 36,
+        ArrowLeft => 37,
+        ArrowUp => 38,
+        ArrowRight => 39,
+        ArrowDown => 40,
+        Insert => 45,
+        Delete => 46,
+        Digit0 => 48,
+        Digit1 => 49,
+        Digit2 => 50,
+        Digit3 => 51,
+        Digit4 => 52,
+        Digit5 => 53,
+        Digit6 => 54,
+        Digit7 => 55,
+        Digit8 => 56,
+        Digit9 => 57,
+        KeyA => 65,
+        KeyB => 66,
+        KeyC => 67,
+        KeyD => 68,
+        KeyE => 69,
+        KeyF => 70,
+        KeyG => 71,
+        KeyH => 72,
+        KeyI => 73,
+        KeyJ => 74,
+        KeyK => 75,
+        KeyL => 76,
+        KeyM => 77,
+        KeyN => 78,
+        KeyO => 79,
+        KeyP => 80,
+        KeyQ => 81,
+        KeyR => 82,
+        KeyS => 83,
+        KeyT => 84,
+        KeyU => 85,
+        KeyV => 86,
+        KeyW => 87,
+        KeyX => 88,
+        KeyY => 89,
+        KeyZ => 90,
+        SuperLeft => 91,
+        SuperRight => 92,
+        Numpad0 => 96,
+        Numpad1 => 97,
+        Numpad2 => 98,
+        Numpad3 => 99,
+        Numpad4 => 100,
+        Numpad5 => 101,
+        Numpad6 => 102,
+        Numpad7 => 103,
+        Numpad8 => 104,
+        Numpad9 => 105,
+        NumpadMultiply => 106,
+        NumpadAdd => 107,
+        NumpadSubtract => 109,
+        NumpadDecimal => 110,
+        NumpadDivide => 111,
+        F1 => 112,
+        F2 => 113,
+        F3 => 114,
+        F4 => 115,
+        F5 => 116,
+        F6 => 117,
+        F7 => 118,
+        F8 => 119,
+        F9 => 120,
+        F10 => 121,
+        F11 => 122,
+        F12 => 123,
+        Semicolon => 186,
+        Equal => 187,
+        Comma => 188,
+        Minus => 189,
+        Period => 190,
+        Slash => 191,
+        Backquote => 192,
+        BracketLeft => 219,
+        Backslash => 220,
+        BracketRight => 221,
+        Quote => 222,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letters_and_digits_map_to_ascii() {
+        assert_eq!(js_key_code(KeyCode::KeyA), Some(b'A'));
+        assert_eq!(js_key_code(KeyCode::KeyZ), Some(b'Z'));
+        assert_eq!(js_key_code(KeyCode::Digit0), Some(b'0'));
+        assert_eq!(js_key_code(KeyCode::Digit9), Some(b'9'));
+    }
+
+    #[test]
+    fn both_sides_share_a_code() {
+        assert_eq!(
+            js_key_code(KeyCode::ShiftLeft),
+            js_key_code(KeyCode::ShiftRight)
+        );
+        assert_eq!(
+            js_key_code(KeyCode::Enter),
+            js_key_code(KeyCode::NumpadEnter)
+        );
+        assert_eq!(js_key_code(KeyCode::ArrowLeft), Some(37));
+        assert_eq!(js_key_code(KeyCode::F12), Some(123));
+    }
+
+    #[test]
+    fn unknown_keys_have_no_code() {
+        assert_eq!(js_key_code(KeyCode::MediaPlayPause), None);
+        assert_eq!(js_key_code(KeyCode::F24), None);
+    }
+}
@@ -0,0 +1,30 @@
+/// Quality used while the camera is dragged, full quality returns once the
+/// drag ends.
+///
+/// Shaders read the current level from `Uniform::quality` and scale their
+/// ray marching step by its inverse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InteractiveLod {
+    /// Quality level in `(0, 1]` exposed to shaders during interaction.
+    pub quality: f32,
+    /// Factor applied on top of the render scale during interaction.
+    pub render_scale: f32,
+}
+
+impl Default for InteractiveLod {
+    fn default() -> Self {
+        Self {
+            quality: 0.5,
+            render_scale: 0.5,
+        }
+    }
+}
+
+impl InteractiveLod {
+    pub fn new(quality: f32, render_scale: f32) -> Self {
+        Self {
+            quality: quality.clamp(0.05, 1.),
+            render_scale: render_scale.clamp(0.25, 1.),
+        }
+    }
+}
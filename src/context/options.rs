@@ -0,0 +1,447 @@
+use crate::{
+    context::{DynamicResolution, InteractiveLod, PresentFilter, RenderMode},
+    Error,
+};
+
+use std::path::PathBuf;
+
+/// Selects a physical device when more than one adapter is available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterSelector {
+    /// First adapter whose name contains the string (case insensitive).
+    Name(String),
+    /// Adapter at the index in the `Instance::enumerate_adapters` list.
+    Index(usize),
+}
+
+impl std::str::FromStr for AdapterSelector {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse::<usize>() {
+            Ok(index) => Self::Index(index),
+            Err(_) => Self::Name(s.to_string()),
+        })
+    }
+}
+
+/// Describes how [`Context`](crate::Context) picks its adapter and device.
+///
+/// Features and limits are requested explicitly: `required_features` and
+/// `limits` must be supported by the adapter, `optional_features` are enabled
+/// only when available. Query [`Context::features`](crate::Context::features)
+/// to see what was actually granted.
+#[derive(Debug, Clone)]
+pub struct ContextOptions {
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    pub adapter: Option<AdapterSelector>,
+    pub required_features: wgpu::Features,
+    pub optional_features: wgpu::Features,
+    pub limits: wgpu::Limits,
+    pub present_mode: wgpu::PresentMode,
+    pub frame_rate_cap: Option<f32>,
+    /// Use a HDR (scRGB) surface when the display supports one.
+    ///
+    /// Only `Rgba16Float` surfaces count as HDR. A 10-bit `Rgb10a2Unorm`
+    /// surface would need the HDR10 (PQ) color space to go beyond SDR
+    /// brightness, and wgpu can't request a surface color space, so without
+    /// scRGB the regular 8-bit SDR path is used.
+    pub hdr: bool,
+    pub paper_white: f32,
+    pub max_luminance: f32,
+    /// Backbuffer resolution relative to the window.
+    pub render_scale: f32,
+    pub present_filter: PresentFilter,
+    /// Adjusts `render_scale` to hold a frame time, requests
+    /// `TIMESTAMP_QUERY` as an optional feature to measure the GPU time.
+    pub dynamic_resolution: Option<DynamicResolution>,
+    pub render_mode: RenderMode,
+    /// Averages up to this many jittered frames while the view is static.
+    pub accumulation: Option<u32>,
+    /// Lowers quality and resolution while the camera is dragged.
+    pub interactive_lod: Option<InteractiveLod>,
+    /// Jitters the camera and resolves frames with temporal anti-aliasing.
+    pub taa: bool,
+    /// JSON file camera bookmarks are loaded from and saved to, see
+    /// [`CameraBookmarks::path_for`](crate::CameraBookmarks::path_for).
+    pub bookmarks: Option<PathBuf>,
+    /// JSON [`InputMap`](crate::InputMap) replacing the default bindings.
+    pub input_map: Option<PathBuf>,
+    /// File [`run`](crate::run) writes the session's input and timing to as
+    /// it goes, see [`Recording`](crate::Recording).
+    pub record: Option<PathBuf>,
+    /// [`Recording`](crate::Recording) [`run`](crate::run) plays instead of
+    /// live input, exiting at its end.
+    pub replay: Option<PathBuf>,
+}
+
+impl Default for ContextOptions {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::PRIMARY,
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            adapter: None,
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            limits: wgpu::Limits::default(),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            frame_rate_cap: None,
+            hdr: false,
+            paper_white: 200.,
+            max_luminance: 1000.,
+            render_scale: 1.,
+            present_filter: PresentFilter::Bilinear,
+            dynamic_resolution: None,
+            render_mode: RenderMode::Continuous,
+            accumulation: None,
+            interactive_lod: None,
+            taa: false,
+            bookmarks: None,
+            input_map: None,
+            record: None,
+            replay: None,
+        }
+    }
+}
+
+impl ContextOptions {
+    /// Backend list, same syntax as `WGPU_BACKEND` (e.g. `vulkan,metal`).
+    pub const ENV_BACKEND: &'static str = "VOKSELIS_BACKEND";
+    /// One of `low`, `high` or `none`.
+    pub const ENV_POWER_PREFERENCE: &'static str = "VOKSELIS_POWER_PREFERENCE";
+    /// Adapter name substring or index.
+    pub const ENV_ADAPTER: &'static str = "VOKSELIS_ADAPTER";
+    /// `on` / `off` or `1` / `0`.
+    pub const ENV_VSYNC: &'static str = "VOKSELIS_VSYNC";
+    /// Maximal frames per second.
+    pub const ENV_FRAME_RATE_CAP: &'static str = "VOKSELIS_FPS_CAP";
+    /// `on` / `off` or `1` / `0`.
+    pub const ENV_HDR: &'static str = "VOKSELIS_HDR";
+    /// Paper white in nits.
+    pub const ENV_PAPER_WHITE: &'static str = "VOKSELIS_PAPER_WHITE";
+    /// Backbuffer resolution relative to the window, e.g. `0.5`.
+    pub const ENV_RENDER_SCALE: &'static str = "VOKSELIS_RENDER_SCALE";
+    /// Target frames per second of the dynamic resolution.
+    pub const ENV_TARGET_FPS: &'static str = "VOKSELIS_TARGET_FPS";
+    /// `continuous` or `on_demand`.
+    pub const ENV_RENDER_MODE: &'static str = "VOKSELIS_RENDER_MODE";
+    /// Maximal number of accumulated frames, `0` disables accumulation.
+    pub const ENV_ACCUMULATION: &'static str = "VOKSELIS_ACCUMULATION";
+    /// `on` / `off` or `1` / `0`.
+    pub const ENV_TAA: &'static str = "VOKSELIS_TAA";
+    /// Path of the camera bookmark file.
+    pub const ENV_BOOKMARKS: &'static str = "VOKSELIS_BOOKMARKS";
+    /// Path of the input map file.
+    pub const ENV_INPUT_MAP: &'static str = "VOKSELIS_INPUT_MAP";
+    /// Path to record the session to.
+    pub const ENV_RECORD: &'static str = "VOKSELIS_RECORD";
+    /// Path of a recording to replay.
+    pub const ENV_REPLAY: &'static str = "VOKSELIS_REPLAY";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn adapter_name(mut self, name: impl Into<String>) -> Self {
+        self.adapter = Some(AdapterSelector::Name(name.into()));
+        self
+    }
+
+    pub fn adapter_index(mut self, index: usize) -> Self {
+        self.adapter = Some(AdapterSelector::Index(index));
+        self
+    }
+
+    pub fn required_features(mut self, features: wgpu::Features) -> Self {
+        self.required_features |= features;
+        self
+    }
+
+    pub fn optional_features(mut self, features: wgpu::Features) -> Self {
+        self.optional_features |= features;
+        self
+    }
+
+    pub fn limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    pub fn vsync(self, vsync: bool) -> Self {
+        self.present_mode(if vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        })
+    }
+
+    pub fn frame_rate_cap(mut self, fps: f32) -> Self {
+        self.frame_rate_cap = Some(fps).filter(|fps| *fps > 0.);
+        self
+    }
+
+    pub fn hdr(mut self, hdr: bool) -> Self {
+        self.hdr = hdr;
+        self
+    }
+
+    /// Luminance in nits of the SDR white and of the display peak used by HDR output.
+    pub fn luminance(mut self, paper_white: f32, max_luminance: f32) -> Self {
+        self.paper_white = paper_white.max(1.);
+        self.max_luminance = max_luminance.max(1.);
+        self
+    }
+
+    pub fn render_scale(mut self, scale: f32) -> Self {
+        self.render_scale = scale;
+        self
+    }
+
+    pub fn present_filter(mut self, filter: PresentFilter) -> Self {
+        self.present_filter = filter;
+        self
+    }
+
+    pub fn dynamic_resolution(mut self, controller: DynamicResolution) -> Self {
+        self.dynamic_resolution = Some(controller);
+        self
+    }
+
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    pub fn accumulation(mut self, max_samples: u32) -> Self {
+        self.accumulation = Some(max_samples).filter(|samples| *samples > 0);
+        self
+    }
+
+    pub fn interactive_lod(mut self, lod: InteractiveLod) -> Self {
+        self.interactive_lod = Some(lod);
+        self
+    }
+
+    pub fn taa(mut self, taa: bool) -> Self {
+        self.taa = taa;
+        self
+    }
+
+    pub fn bookmarks(mut self, path: impl Into<PathBuf>) -> Self {
+        self.bookmarks = Some(path.into());
+        self
+    }
+
+    pub fn input_map(mut self, path: impl Into<PathBuf>) -> Self {
+        self.input_map = Some(path.into());
+        self
+    }
+
+    pub fn record(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record = Some(path.into());
+        self
+    }
+
+    pub fn replay(mut self, path: impl Into<PathBuf>) -> Self {
+        self.replay = Some(path.into());
+        self
+    }
+
+    /// Overrides fields with the values of the `VOKSELIS_*` environment variables
+    /// that are set. Malformed values are ignored.
+    pub fn with_env(mut self) -> Self {
+        if let Ok(backends) = std::env::var(Self::ENV_BACKEND) {
+            let backends = wgpu::util::parse_backends_from_comma_list(&backends.to_lowercase());
+            if !backends.is_empty() {
+                self.backends = backends;
+            }
+        }
+        if let Ok(power) = std::env::var(Self::ENV_POWER_PREFERENCE) {
+            match power.to_lowercase().as_str() {
+                "low" => self.power_preference = wgpu::PowerPreference::LowPower,
+                "high" => self.power_preference = wgpu::PowerPreference::HighPerformance,
+                "none" => self.power_preference = wgpu::PowerPreference::None,
+                _ => {}
+            }
+        }
+        if let Ok(adapter) = std::env::var(Self::ENV_ADAPTER) {
+            if !adapter.is_empty() {
+                self.adapter = adapter.parse().ok();
+            }
+        }
+        if let Ok(vsync) = std::env::var(Self::ENV_VSYNC) {
+            match vsync.to_lowercase().as_str() {
+                "1" | "on" | "true" => self = self.vsync(true),
+                "0" | "off" | "false" => self = self.vsync(false),
+                _ => {}
+            }
+        }
+        if let Some(fps) = std::env::var(Self::ENV_FRAME_RATE_CAP)
+            .ok()
+            .and_then(|fps| fps.parse().ok())
+        {
+            self = self.frame_rate_cap(fps);
+        }
+        if let Ok(hdr) = std::env::var(Self::ENV_HDR) {
+            match hdr.to_lowercase().as_str() {
+                "1" | "on" | "true" => self.hdr = true,
+                "0" | "off" | "false" => self.hdr = false,
+                _ => {}
+            }
+        }
+        if let Some(nits) = std::env::var(Self::ENV_PAPER_WHITE)
+            .ok()
+            .and_then(|nits| nits.parse().ok())
+        {
+            let max_luminance = self.max_luminance;
+            self = self.luminance(nits, max_luminance);
+        }
+        if let Some(scale) = std::env::var(Self::ENV_RENDER_SCALE)
+            .ok()
+            .and_then(|scale| scale.parse().ok())
+        {
+            self.render_scale = scale;
+        }
+        if let Some(fps) = std::env::var(Self::ENV_TARGET_FPS)
+            .ok()
+            .and_then(|fps| fps.parse::<f32>().ok())
+            .filter(|fps| *fps > 0.)
+        {
+            self.dynamic_resolution = Some(DynamicResolution::with_fps(fps));
+        }
+        if let Ok(mode) = std::env::var(Self::ENV_RENDER_MODE) {
+            match mode.to_lowercase().as_str() {
+                "continuous" => self.render_mode = RenderMode::Continuous,
+                "on_demand" | "ondemand" => self.render_mode = RenderMode::OnDemand,
+                _ => {}
+            }
+        }
+        if let Some(samples) = std::env::var(Self::ENV_ACCUMULATION)
+            .ok()
+            .and_then(|samples| samples.parse().ok())
+        {
+            self = self.accumulation(samples);
+        }
+        if let Ok(taa) = std::env::var(Self::ENV_TAA) {
+            match taa.to_lowercase().as_str() {
+                "1" | "on" | "true" => self.taa = true,
+                "0" | "off" | "false" => self.taa = false,
+                _ => {}
+            }
+        }
+        if let Ok(path) = std::env::var(Self::ENV_BOOKMARKS) {
+            if !path.is_empty() {
+                self = self.bookmarks(path);
+            }
+        }
+        if let Ok(path) = std::env::var(Self::ENV_INPUT_MAP) {
+            if !path.is_empty() {
+                self = self.input_map(path);
+            }
+        }
+        if let Ok(path) = std::env::var(Self::ENV_RECORD) {
+            if !path.is_empty() {
+                self = self.record(path);
+            }
+        }
+        if let Ok(path) = std::env::var(Self::ENV_REPLAY) {
+            if !path.is_empty() {
+                self = self.replay(path);
+            }
+        }
+        self
+    }
+
+    pub(crate) async fn request_adapter(
+        &self,
+        instance: &wgpu::Instance,
+        surface: &wgpu::Surface,
+    ) -> Result<wgpu::Adapter, Error> {
+        let Some(selector) = &self.adapter else {
+            return instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: self.power_preference,
+                    force_fallback_adapter: false,
+                    compatible_surface: Some(surface),
+                })
+                .await
+                .ok_or(Error::AdapterNotFound {
+                    backends: self.backends,
+                    selector: None,
+                });
+        };
+
+        let adapters = instance.enumerate_adapters(self.backends);
+        let adapter = match selector {
+            AdapterSelector::Index(index) => adapters.into_iter().nth(*index),
+            AdapterSelector::Name(name) => {
+                let name = name.to_lowercase();
+                adapters
+                    .into_iter()
+                    .find(|adapter| adapter.get_info().name.to_lowercase().contains(&name))
+            }
+        }
+        .ok_or_else(|| Error::AdapterNotFound {
+            backends: self.backends,
+            selector: Some(selector.clone()),
+        })?;
+
+        if !adapter.is_surface_supported(surface) {
+            return Err(Error::SurfaceUnsupported {
+                adapter: adapter.get_info().name,
+            });
+        }
+        Ok(adapter)
+    }
+
+    /// Resolves the features to request from `adapter`, failing when a required
+    /// feature or limit is not supported.
+    pub(crate) fn device_features(&self, adapter: &wgpu::Adapter) -> Result<wgpu::Features, Error> {
+        let supported = adapter.features();
+
+        let missing = self.required_features - supported;
+        if !missing.is_empty() {
+            return Err(Error::MissingFeatures {
+                adapter: adapter.get_info().name,
+                features: missing,
+            });
+        }
+
+        let mut unmet = vec![];
+        self.limits.check_limits_with_fail_fn(
+            &adapter.limits(),
+            false,
+            |limit, requested, allowed| {
+                unmet.push((limit, requested, allowed));
+            },
+        );
+        if !unmet.is_empty() {
+            return Err(Error::UnsupportedLimits {
+                adapter: adapter.get_info().name,
+                limits: unmet,
+            });
+        }
+
+        let mut optional = self.optional_features;
+        if self.dynamic_resolution.is_some() {
+            optional |= wgpu::Features::TIMESTAMP_QUERY;
+        }
+        Ok(self.required_features | (optional & supported))
+    }
+}
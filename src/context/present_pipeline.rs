@@ -1,9 +1,47 @@
-use crate::{GlobalUniformBinding, Uniform};
+use crate::{GlobalUniformBinding, NonZeroSized, Uniform};
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Output settings of the present pass, only used by the HDR path.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub(crate) struct PresentUniform {
+    /// Luminance of the SDR white in nits.
+    pub(crate) paper_white: f32,
+    /// Peak luminance of the display in nits.
+    pub(crate) max_luminance: f32,
+    /// [`PresentFilter`] used to upscale the backbuffer.
+    pub(crate) filter: u32,
+    _padding: f32,
+}
+
+impl PresentUniform {
+    pub(crate) fn new(paper_white: f32, max_luminance: f32, filter: PresentFilter) -> Self {
+        Self {
+            paper_white,
+            max_luminance,
+            filter: filter as _,
+            _padding: 0.,
+        }
+    }
+}
+
+/// Filter used to fit the backbuffer to the surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentFilter {
+    #[default]
+    Bilinear = 0,
+    /// Sharper when upscaling a backbuffer rendered below window resolution.
+    Bicubic = 1,
+}
 
 pub(crate) struct PresentPipeline {
     pub(crate) pipeline: wgpu::RenderPipeline,
     _surface_format: wgpu::TextureFormat,
     sampler_bind_group: wgpu::BindGroup,
+    settings_buffer: wgpu::Buffer,
+    settings_bind_group: wgpu::BindGroup,
 }
 
 impl PresentPipeline {
@@ -11,6 +49,7 @@ impl PresentPipeline {
         device: &wgpu::Device,
         surface_format: wgpu::TextureFormat,
         module_desc: wgpu::ShaderModuleDescriptor<'_>,
+        settings: PresentUniform,
     ) -> Self {
         let shader = device.create_shader_module(module_desc);
         let global_bind_group_layout = device.create_bind_group_layout(&Uniform::DESC);
@@ -38,12 +77,27 @@ impl PresentPipeline {
                     count: None,
                 }],
             });
+        let settings_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Present Settings BGL"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: Some(PresentUniform::SIZE),
+                    },
+                    count: None,
+                }],
+            });
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Screen Pass Layout"),
             bind_group_layouts: &[
                 &global_bind_group_layout,
                 &texture_bind_group_layout,
                 &sampler_bind_group_layout,
+                &settings_bind_group_layout,
             ],
             push_constant_ranges: &[],
         });
@@ -52,7 +106,7 @@ impl PresentPipeline {
             layout: Some(&layout),
             fragment: Some(wgpu::FragmentState {
                 module: &shader,
-                entry_point: "fs_main",
+                entry_point: Self::entry_point(surface_format),
                 targets: &[
                     Some(wgpu::ColorTargetState {
                         format: surface_format,
@@ -99,12 +153,49 @@ impl PresentPipeline {
             }],
         });
 
+        let settings_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Present Settings"),
+            contents: bytemuck::bytes_of(&settings),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let settings_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Present Settings Bind Group"),
+            layout: &settings_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: settings_buffer.as_entire_binding(),
+            }],
+        });
+
         Self {
             pipeline,
             _surface_format: surface_format,
             sampler_bind_group,
+            settings_buffer,
+            settings_bind_group,
         }
     }
+
+    /// Whether the surface format can hold values above the SDR white.
+    pub(crate) fn is_hdr_format(format: wgpu::TextureFormat) -> bool {
+        format == wgpu::TextureFormat::Rgba16Float
+    }
+
+    fn entry_point(surface_format: wgpu::TextureFormat) -> &'static str {
+        if Self::is_hdr_format(surface_format) {
+            // Linear scRGB, no tone mapping
+            "fs_main_hdr"
+        } else if surface_format.is_srgb() {
+            // The sRGB surface already encodes on write
+            "fs_main_srgb"
+        } else {
+            "fs_main"
+        }
+    }
+
+    pub(crate) fn update_settings(&self, queue: &wgpu::Queue, settings: &PresentUniform) {
+        queue.write_buffer(&self.settings_buffer, 0, bytemuck::bytes_of(settings));
+    }
 }
 
 impl<'a> PresentPipeline {
@@ -120,6 +211,7 @@ impl<'a> PresentPipeline {
         rpass.set_bind_group(0, &uniform_bind_group.binding, &[]);
         rpass.set_bind_group(1, input_texture_binding, &[]);
         rpass.set_bind_group(2, &self.sampler_bind_group, &[]);
+        rpass.set_bind_group(3, &self.settings_bind_group, &[]);
         rpass.draw(0..3, 0..1);
     }
 }
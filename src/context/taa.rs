@@ -0,0 +1,269 @@
+use crate::{
+    utils::halton, CameraBinding, GlobalUniformBinding, HdrBackBuffer, NonZeroSized, Uniform,
+};
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Number of sub-pixel positions the camera cycles through.
+const JITTER_SEQUENCE: u32 = 16;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct TaaUniform {
+    /// NDC depth of the camera target, used to reproject without a depth buffer.
+    focus_depth: f32,
+    /// Weight of the current frame.
+    blend: f32,
+    history_valid: u32,
+    _padding: f32,
+}
+
+struct History {
+    backbuffer: HdrBackBuffer,
+    bind_group: wgpu::BindGroup,
+}
+
+/// Temporal anti-aliasing resolve of the jittered backbuffer.
+///
+/// Blends each frame with the history reprojected through
+/// `CameraUniform::prev_proj_view`, clamped to the neighbourhood of the
+/// current pixel to avoid ghosting.
+pub(crate) struct Taa {
+    pipeline: wgpu::RenderPipeline,
+    history_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    settings_buffer: wgpu::Buffer,
+    history: [History; 2],
+    current: usize,
+    history_valid: bool,
+}
+
+impl Taa {
+    const BLEND: f32 = 0.1;
+
+    const HISTORY_DESC: wgpu::BindGroupLayoutDescriptor<'static> =
+        wgpu::BindGroupLayoutDescriptor {
+            label: Some("TAA History Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: Some(TaaUniform::SIZE),
+                    },
+                    count: None,
+                },
+            ],
+        };
+
+    pub(crate) fn new(device: &wgpu::Device, resolution: (u32, u32)) -> Self {
+        let shader = device.create_shader_module(CameraBinding::shader_module_desc(
+            "taa.wgsl",
+            include_str!("../../shaders/taa.wgsl"),
+        ));
+        let global_bind_group_layout = device.create_bind_group_layout(&Uniform::DESC);
+        let camera_bind_group_layout = device.create_bind_group_layout(&CameraBinding::DESC);
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&HdrBackBuffer::DESC_RENDER);
+        let history_layout = device.create_bind_group_layout(&Self::HISTORY_DESC);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("TAA Pass Layout"),
+            bind_group_layouts: &[
+                &global_bind_group_layout,
+                &camera_bind_group_layout,
+                &texture_bind_group_layout,
+                &history_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("TAA Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HdrBackBuffer::FORMAT,
+                    blend: None,
+                    write_mask: Default::default(),
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("TAA History Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let settings_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("TAA Settings"),
+            contents: bytemuck::bytes_of(&TaaUniform::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let history = [
+            create_history(
+                device,
+                &history_layout,
+                &sampler,
+                &settings_buffer,
+                resolution,
+            ),
+            create_history(
+                device,
+                &history_layout,
+                &sampler,
+                &settings_buffer,
+                resolution,
+            ),
+        ];
+
+        Self {
+            pipeline,
+            history_layout,
+            sampler,
+            settings_buffer,
+            history,
+            current: 0,
+            history_valid: false,
+        }
+    }
+
+    /// Sub-pixel offset of the camera rays for `frame`, in `[-0.5, 0.5)`.
+    pub(crate) fn jitter(frame: u32) -> [f32; 2] {
+        let index = frame % JITTER_SEQUENCE + 1;
+        [halton(2, index) - 0.5, halton(3, index) - 0.5]
+    }
+
+    /// Drops the history, e.g. after a cut.
+    pub(crate) fn reset(&mut self) {
+        self.history_valid = false;
+    }
+
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, resolution: (u32, u32)) {
+        if resolution == self.history[0].backbuffer.size() {
+            return;
+        }
+        for history in &mut self.history {
+            *history = create_history(
+                device,
+                &self.history_layout,
+                &self.sampler,
+                &self.settings_buffer,
+                resolution,
+            );
+        }
+        self.reset();
+    }
+
+    /// Resolves `backbuffer` against the history, `focus_depth` is the NDC
+    /// depth history is reprojected at.
+    pub(crate) fn record(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        uniform: &GlobalUniformBinding,
+        camera: &CameraBinding,
+        backbuffer: &HdrBackBuffer,
+        focus_depth: f32,
+    ) {
+        let settings = TaaUniform {
+            focus_depth,
+            blend: Self::BLEND,
+            history_valid: self.history_valid as _,
+            _padding: 0.,
+        };
+        queue.write_buffer(&self.settings_buffer, 0, bytemuck::bytes_of(&settings));
+
+        let next = 1 - self.current;
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("TAA Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.history[next].backbuffer.texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &uniform.binding, &[]);
+        rpass.set_bind_group(1, &camera.bind_group, &[]);
+        rpass.set_bind_group(2, &backbuffer.render_bind_group, &[]);
+        rpass.set_bind_group(3, &self.history[self.current].bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+        drop(rpass);
+
+        self.current = next;
+        self.history_valid = true;
+    }
+
+    /// Anti-aliased image of the last recorded frame.
+    pub(crate) fn output(&self) -> &HdrBackBuffer {
+        &self.history[self.current].backbuffer
+    }
+}
+
+fn create_history(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    settings_buffer: &wgpu::Buffer,
+    resolution: (u32, u32),
+) -> History {
+    let backbuffer = HdrBackBuffer::new(device, resolution);
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("TAA History Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&backbuffer.texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: settings_buffer.as_entire_binding(),
+            },
+        ],
+    });
+    History {
+        backbuffer,
+        bind_group,
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{camera::CameraUniform, utils::dispatch_optimal, Context, NonZeroSized};
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Top left corner of a tile in pixels, read by the shader through a
+/// dynamic storage buffer offset.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct TileOffset {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Splits a compute dispatch over the backbuffer into square tiles and
+/// spreads them across frames.
+///
+/// The shader binds a [`TileOffset`] at the group passed to
+/// [`TiledDispatcher::dispatch`] and adds it to `global_invocation_id`.
+/// Rendering restarts whenever the camera or the global uniform (besides
+/// time) change. While the camera moves or the user interacts the whole
+/// image is dispatched at once, so no stale tiles show. Other changes, e.g.
+/// a resize or [`InteractiveLod`](crate::InteractiveLod) restoring the full
+/// quality, spread the tiles over frames until every tile is up to date.
+pub struct TiledDispatcher {
+    tile_size: u32,
+    workgroup_size: (u32, u32),
+    tiles_per_frame: Option<u32>,
+
+    min_alignment: u32,
+    resolution: (u32, u32),
+    bind_group: wgpu::BindGroup,
+    aligned_offset: u32,
+    tile_count: u32,
+    next_tile: u32,
+    /// Set when the camera moved or the user interacts, the next dispatch
+    /// covers every tile.
+    whole_frame: bool,
+
+    last_camera: CameraUniform,
+    last_uniform: [f32; 9],
+}
+
+impl TiledDispatcher {
+    pub const DESC: wgpu::BindGroupLayoutDescriptor<'static> = wgpu::BindGroupLayoutDescriptor {
+        label: Some("Tile Offset Bind Group Layout"),
+        entries: &[wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: true,
+                min_binding_size: Some(TileOffset::SIZE),
+            },
+            count: None,
+        }],
+    };
+
+    /// `workgroup_size` has to match the `@workgroup_size` of the entry point.
+    pub fn new(ctx: &Context, tile_size: u32, workgroup_size: (u32, u32)) -> Self {
+        let min_alignment = ctx.limits.min_storage_buffer_offset_alignment;
+        let resolution = ctx.render_backbuffer.size();
+        let tile_size = tile_size.max(1);
+        let (bind_group, aligned_offset, tile_count) =
+            create_offsets(&ctx.device, resolution, tile_size, min_alignment);
+
+        Self {
+            tile_size,
+            workgroup_size,
+            tiles_per_frame: None,
+
+            min_alignment,
+            resolution,
+            bind_group,
+            aligned_offset,
+            tile_count,
+            next_tile: 0,
+            whole_frame: false,
+
+            last_camera: ctx.camera.get_proj_view_matrix(),
+            last_uniform: ctx.global_uniform.scene_state(),
+        }
+    }
+
+    /// Limits the number of tiles dispatched each frame, `None` renders
+    /// the whole image at once.
+    ///
+    /// Frames after the camera moved or while the user interacts ignore the
+    /// limit, tiling them would leave most of the image showing older views.
+    pub fn with_tiles_per_frame(mut self, tiles: Option<u32>) -> Self {
+        self.set_tiles_per_frame(tiles);
+        self
+    }
+
+    pub fn set_tiles_per_frame(&mut self, tiles: Option<u32>) {
+        self.tiles_per_frame = tiles.map(|tiles| tiles.max(1));
+    }
+
+    pub fn tile_count(&self) -> u32 {
+        self.tile_count
+    }
+
+    /// Whether every tile was rendered since the last restart.
+    pub fn is_complete(&self) -> bool {
+        self.next_tile >= self.tile_count
+    }
+
+    /// Starts over from the first tile.
+    pub fn restart(&mut self) {
+        self.next_tile = 0;
+    }
+
+    /// Recomputes the tiles when the backbuffer size changed.
+    pub fn resize(&mut self, ctx: &Context) {
+        let resolution = ctx.render_backbuffer.size();
+        if resolution == self.resolution {
+            return;
+        }
+        self.resolution = resolution;
+        (self.bind_group, self.aligned_offset, self.tile_count) =
+            create_offsets(&ctx.device, resolution, self.tile_size, self.min_alignment);
+        self.restart();
+    }
+
+    /// Restarts if the camera or the global uniform changed since the last call
+    /// and keeps frames coming until every tile is rendered.
+    pub fn update(&mut self, ctx: &Context) {
+        self.resize(ctx);
+
+        let camera = ctx.camera.get_proj_view_matrix();
+        let uniform = ctx.global_uniform.scene_state();
+        let camera_changed = bytemuck::bytes_of(&camera) != bytemuck::bytes_of(&self.last_camera);
+        if camera_changed || uniform != self.last_uniform {
+            self.last_camera = camera;
+            self.last_uniform = uniform;
+            self.restart();
+            self.whole_frame |= camera_changed || ctx.is_interacting();
+        }
+        if !self.is_complete() {
+            ctx.request_redraw();
+        }
+    }
+
+    /// Dispatches the next batch of tiles, `group` is the bind group index
+    /// of the tile offset in the pipeline layout.
+    pub fn dispatch<'a>(&'a mut self, cpass: &mut wgpu::ComputePass<'a>, group: u32) {
+        let budget = match self.tiles_per_frame {
+            Some(tiles) if !self.whole_frame => tiles,
+            _ => self.tile_count,
+        };
+        self.whole_frame = false;
+        let end = self.next_tile.saturating_add(budget).min(self.tile_count);
+        let (x, y) = self.workgroup_size;
+        for tile in self.next_tile..end {
+            cpass.set_bind_group(group, &self.bind_group, &[tile * self.aligned_offset]);
+            cpass.dispatch_workgroups(
+                dispatch_optimal(self.tile_size, x),
+                dispatch_optimal(self.tile_size, y),
+                1,
+            );
+        }
+        self.next_tile = end;
+    }
+}
+
+/// Tile offsets padded to `min_alignment`, row by row, and the stride
+/// between them.
+fn tile_offsets((width, height): (u32, u32), tile_size: u32, min_alignment: u32) -> (Vec<u8>, u32) {
+    let size = TileOffset::SIZE.get() as u32;
+    let padding = (min_alignment - size % min_alignment) % min_alignment;
+    let aligned_offset = size + padding;
+
+    let mut offsets = vec![];
+    for y in 0..dispatch_optimal(height, tile_size) {
+        for x in 0..dispatch_optimal(width, tile_size) {
+            offsets.extend_from_slice(bytemuck::bytes_of(&TileOffset {
+                x: (x * tile_size) as f32,
+                y: (y * tile_size) as f32,
+            }));
+            offsets.extend(vec![0; padding as _]);
+        }
+    }
+    (offsets, aligned_offset)
+}
+
+fn create_offsets(
+    device: &wgpu::Device,
+    resolution: (u32, u32),
+    tile_size: u32,
+    min_alignment: u32,
+) -> (wgpu::BindGroup, u32, u32) {
+    let (offsets, aligned_offset) = tile_offsets(resolution, tile_size, min_alignment);
+    let tile_count = offsets.len() as u32 / aligned_offset;
+
+    let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Tile Offsets Buffer"),
+        contents: &offsets,
+        usage: wgpu::BufferUsages::STORAGE,
+    });
+    let layout = device.create_bind_group_layout(&TiledDispatcher::DESC);
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Tile Offsets Bind Group"),
+        layout: &layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &buffer,
+                offset: 0,
+                size: Some(TileOffset::SIZE),
+            }),
+        }],
+    });
+
+    (bind_group, aligned_offset, tile_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(resolution: (u32, u32), tile_size: u32, min_alignment: u32) -> Vec<TileOffset> {
+        let (bytes, aligned_offset) = tile_offsets(resolution, tile_size, min_alignment);
+        bytes
+            .chunks(aligned_offset as _)
+            .map(|tile| bytemuck::pod_read_unaligned(&tile[..TileOffset::SIZE.get() as _]))
+            .collect()
+    }
+
+    #[test]
+    fn tiles_cover_partial_edges() {
+        let tiles = offsets((100, 50), 64, 8);
+        assert_eq!(
+            tiles,
+            [TileOffset { x: 0., y: 0. }, TileOffset { x: 64., y: 0. }]
+        );
+        assert_eq!(offsets((128, 129), 64, 8).len(), 6);
+        assert_eq!(offsets((1, 1), 64, 8).len(), 1);
+    }
+
+    #[test]
+    fn tiles_are_aligned() {
+        let (bytes, aligned_offset) = tile_offsets((256, 256), 64, 256);
+        assert_eq!(aligned_offset, 256);
+        assert_eq!(bytes.len(), 16 * 256);
+        assert_eq!(
+            offsets((256, 256), 64, 256)[5],
+            TileOffset { x: 64., y: 64. }
+        );
+    }
+}
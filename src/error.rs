@@ -0,0 +1,161 @@
+use crate::AdapterSelector;
+
+use std::{fmt, path::PathBuf};
+
+/// Errors produced while setting up or running a [`Demo`](crate::Demo).
+#[derive(Debug)]
+pub enum Error {
+    /// No adapter matched the [`ContextOptions`](crate::ContextOptions).
+    AdapterNotFound {
+        backends: wgpu::Backends,
+        selector: Option<AdapterSelector>,
+    },
+    /// The chosen adapter can't present to the window surface.
+    SurfaceUnsupported {
+        adapter: String,
+    },
+    /// The adapter lacks some of the required features.
+    MissingFeatures {
+        adapter: String,
+        features: wgpu::Features,
+    },
+    /// The adapter can't satisfy some of the requested limits, given as
+    /// name, requested and allowed value.
+    UnsupportedLimits {
+        adapter: String,
+        limits: Vec<(&'static str, u64, u64)>,
+    },
+    CreateSurface(wgpu::CreateSurfaceError),
+    RequestDevice(wgpu::RequestDeviceError),
+    Surface(wgpu::SurfaceError),
+    EventLoop(winit::error::EventLoopError),
+    Window(winit::error::OsError),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Image(image::ImageError),
+    /// An image without texels can't become a texture.
+    EmptyImage {
+        width: u32,
+        height: u32,
+    },
+    /// The bookmark file failed to load, saving would overwrite it.
+    BookmarksUnreadable {
+        path: PathBuf,
+    },
+    /// A shader or its pipeline failed validation.
+    Shader {
+        label: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AdapterNotFound {
+                backends,
+                selector: Some(selector),
+            } => write!(
+                f,
+                "No adapter matches {selector:?} on backends {backends:?}"
+            ),
+            Self::AdapterNotFound {
+                backends,
+                selector: None,
+            } => write!(
+                f,
+                "No adapter compatible with the window surface on backends {backends:?}"
+            ),
+            Self::SurfaceUnsupported { adapter } => {
+                write!(
+                    f,
+                    "Adapter `{adapter}` can not present to the window surface"
+                )
+            }
+            Self::MissingFeatures { adapter, features } => write!(
+                f,
+                "Adapter `{adapter}` does not support required features: {features:?}"
+            ),
+            Self::UnsupportedLimits { adapter, limits } => {
+                write!(f, "Adapter `{adapter}` does not support requested limits:")?;
+                for (i, (name, requested, allowed)) in limits.iter().enumerate() {
+                    let separator = if i == 0 { " " } else { ", " };
+                    write!(
+                        f,
+                        "{separator}{name} {requested} (adapter allows {allowed})"
+                    )?;
+                }
+                Ok(())
+            }
+            Self::CreateSurface(e) => write!(f, "Failed to create the window surface: {e}"),
+            Self::RequestDevice(e) => write!(f, "Failed to request a device: {e}"),
+            Self::Surface(e) => write!(f, "Failed to acquire the next surface texture: {e}"),
+            Self::EventLoop(e) => write!(f, "Event loop error: {e}"),
+            Self::Window(e) => write!(f, "Failed to create a window: {e}"),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Json(e) => write!(f, "Malformed JSON: {e}"),
+            Self::Image(e) => write!(f, "Failed to decode image: {e}"),
+            Self::EmptyImage { width, height } => {
+                write!(f, "Can not create a texture from a {width}x{height} image")
+            }
+            Self::BookmarksUnreadable { path } => write!(
+                f,
+                "Camera bookmarks at {} failed to load, not overwriting them",
+                path.display()
+            ),
+            Self::Shader { label, message } => write!(f, "Failed to compile `{label}`: {message}"),
+        }
+    }
+}
+
+/// Wrapped errors are part of the message, so there is no `source` that
+/// chained reporters would print a second time.
+impl std::error::Error for Error {}
+
+impl From<wgpu::CreateSurfaceError> for Error {
+    fn from(e: wgpu::CreateSurfaceError) -> Self {
+        Self::CreateSurface(e)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for Error {
+    fn from(e: wgpu::RequestDeviceError) -> Self {
+        Self::RequestDevice(e)
+    }
+}
+
+impl From<wgpu::SurfaceError> for Error {
+    fn from(e: wgpu::SurfaceError) -> Self {
+        Self::Surface(e)
+    }
+}
+
+impl From<winit::error::EventLoopError> for Error {
+    fn from(e: winit::error::EventLoopError) -> Self {
+        Self::EventLoop(e)
+    }
+}
+
+impl From<winit::error::OsError> for Error {
+    fn from(e: winit::error::OsError) -> Self {
+        Self::Window(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        Self::Image(e)
+    }
+}
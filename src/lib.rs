@@ -47,32 +47,76 @@ clippy::suspicious,
 
 pub mod camera;
 pub mod context;
+mod error;
+pub mod shadertoy;
 mod utils;
 
-pub use camera::{Camera, CameraBinding};
-pub use context::{Context, GlobalUniformBinding, HdrBackBuffer, Uniform, VolumeTexture};
+pub use camera::{
+    AnimationController, ArcballController, Bookmark, Camera, CameraAnimation, CameraBinding,
+    CameraBookmarks, CameraController, CameraKeyframe, CameraMode, CameraView, FlyController,
+    Interpolation, OrbitController, Playback, Projection,
+};
+pub use context::{
+    js_key_code, AdapterSelector, Context, ContextOptions, DynamicResolution, FeedbackTexture,
+    GlobalUniformBinding, GpuTimer, HdrBackBuffer, ImageTexture, InputBinding, InputUniform,
+    InteractiveLod, PresentFilter, RenderMode, TextureOptions, TileOffset, TiledDispatcher,
+    Uniform, VolumeTexture,
+};
+pub use error::Error;
+pub use shadertoy::{
+    Channel, ChannelInput, Shadertoy, ShadertoyBuffer, ShadertoyDesc, ShadertoyPass,
+};
+pub use utils::input::{
+    AxisBinding, Binding, Input, InputEvent, InputMap, Modifier, ScrollDelta, Trigger,
+};
+pub use utils::recording::{FrameTiming, RecordedTick, Recording, RecordingHeader};
 pub use utils::{dispatch_optimal, NonZeroSized};
 
 use pollster::FutureExt;
-use utils::{frame_counter::FrameCounter, input::Input};
+use utils::{
+    frame_counter::FrameCounter,
+    recording::{Recorder, Replay},
+};
 use winit::{
-    dpi::{PhysicalPosition, PhysicalSize},
-    event::{
-        DeviceEvent, ElementState, Event, KeyEvent, MouseScrollDelta, WindowEvent,
-    },
-    event_loop::{ControlFlow, EventLoop},
+    dpi::PhysicalSize,
+    event::{DeviceEvent, Event, KeyEvent, WindowEvent},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
     keyboard::Key,
     window::Window,
 };
 
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 use winit::keyboard::NamedKey;
 
 pub trait Demo: 'static + Sized {
-    fn init(ctx: &mut Context) -> Self;
-    fn resize(&mut self, _: &wgpu::Device, _: &wgpu::Queue, _: &wgpu::SurfaceConfiguration) {}
+    /// Adapter and device requirements of the demo.
+    ///
+    /// `VOKSELIS_*` environment variables are applied on top, see [`ContextOptions::with_env`].
+    fn options() -> ContextOptions {
+        ContextOptions::default()
+    }
+    /// Errors, e.g. of shaders or assets that failed to load, are returned
+    /// from [`run`].
+    fn init(ctx: &mut Context) -> Result<Self, Error>;
+    /// Called after the device was lost and [`Context`] recreated it.
+    ///
+    /// Every resource of the old device is unusable, so by default the demo
+    /// is initialised from scratch, which also re-uploads its volumes.
+    fn reinit(&mut self, ctx: &mut Context) -> Result<(), Error> {
+        *self = Self::init(ctx)?;
+        Ok(())
+    }
+    /// Called after the window and [`Context::render_backbuffer`] were resized.
+    fn resize(&mut self, _: &Context) {}
     fn update(&mut self, _: &mut Context) {}
+    /// Raw window events, not called while replaying a [`Recording`], query
+    /// [`Context::input`] to react to replayed input too.
     fn update_input(&mut self, _: WindowEvent) {}
+    /// Called with a recompiled shader module sent through the event loop proxy.
+    fn update_shader(&mut self, _: &Context, _: &Path, _: wgpu::ShaderModule) {}
     fn render(&mut self, _: &Context) {}
 }
 
@@ -80,111 +124,317 @@ pub fn run<D: Demo>(
     event_loop: EventLoop<(PathBuf, wgpu::ShaderModule)>,
     window: Window,
     camera: Option<Camera>,
-) -> Result<(), String> {
+) -> Result<(), Error> {
     env_logger::init();
 
-    let mut context = Context::new(&window, camera).block_on()?;
-
-    let mut frame_counter = FrameCounter::new();
-    let mut input = Input::new();
+    let options = D::options().with_env();
+    let mut context = Context::new(&window, camera, &options).block_on()?;
 
-    let mut mouse_dragged = false;
-    let rotate_speed = 0.0025;
-    let zoom_speed = 0.002;
+    let demo = D::init(&mut context)?;
 
-    let mut demo = D::init(&mut context);
+    let replay = match &options.replay {
+        Some(path) => {
+            let recording = Recording::load(path)?;
+            let (width, height) = recording.header.window_size;
+            let _ = window.request_inner_size(PhysicalSize::new(width, height));
+            Some(Replay::new(recording, &mut context))
+        }
+        None => None,
+    };
+    let recorder = options
+        .record
+        .as_ref()
+        .map(|path| Recorder::new(path, &context))
+        .transpose()?;
 
-    let mut main_window_focused = false;
-    event_loop.run(move |event, target| {
+    let mut runner = Runner {
+        window,
+        context,
+        demo,
+        frame_counter: FrameCounter::new(),
+        replay,
+        recorder,
+        exit_error: None,
+        main_window_focused: false,
+        last_frame: Instant::now(),
+        last_update: Instant::now(),
+    };
+    event_loop.run(|event, target| {
         target.set_control_flow(ControlFlow::Wait);
 
         match event {
-            Event::AboutToWait => {
-                context.update(&frame_counter, &input);
-                demo.update(&mut context);
-                window.request_redraw();
-            },
+            Event::AboutToWait => runner.about_to_wait(target),
+
+            Event::UserEvent((path, module)) => {
+                runner.demo.update_shader(&runner.context, &path, module);
+                runner.context.request_redraw();
+            }
 
             Event::WindowEvent {
-                event: window_event, window_id, ..
-            } if window.id() == window_id => {
-                input.update(&window_event, &window);
-
-                match window_event {
-                    WindowEvent::Focused(focused) => main_window_focused = focused,
-
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        event:
-                            KeyEvent {
-                                logical_key: Key::Named(NamedKey::Escape),
-                                ..
-                            },
-                        ..
-                    } => target.exit(),
-
-                    WindowEvent::RedrawRequested => {
-                        frame_counter.record();
-
-                        demo.render(&context);
-
-                        match context.render() {
-                            Ok(_) => {}
-                            Err(wgpu::SurfaceError::Lost) => {
-                                context.resize(context.width, context.height);
-                                window.request_redraw();
-                            }
-                            Err(wgpu::SurfaceError::OutOfMemory) => target.exit(),
-                            Err(e) => {
-                                eprintln!("{:?}", e);
-                                window.request_redraw();
-                            }
-                        }
-                    }
-
-                    WindowEvent::Resized(PhysicalSize { width, height }) => {
-                        if width != 0 && height != 0 {
-                            context.resize(width, height);
-                            demo.resize(&context.device, &context.queue, &context.surface_config);
-                        }
-                    }
-
-                    _ => {}
-                }
-                demo.update_input(window_event);
+                event: window_event,
+                window_id,
+            } if runner.window.id() == window_id => runner.window_event(window_event, target),
+
+            Event::DeviceEvent {
+                event: device_event,
+                ..
+            } => runner.device_event(&device_event),
+
+            _ => {}
+        }
+    })?;
+
+    runner.exit_error.map_or(Ok(()), Err)
+}
+
+type Target = EventLoopWindowTarget<(PathBuf, wgpu::ShaderModule)>;
+
+/// State of the event loop driven by [`run`].
+struct Runner<D> {
+    window: Window,
+    context: Context,
+    demo: D,
+    frame_counter: FrameCounter,
+    replay: Option<Replay>,
+    recorder: Option<Recorder>,
+    exit_error: Option<Error>,
+    main_window_focused: bool,
+    last_frame: Instant,
+    last_update: Instant,
+}
+
+impl<D: Demo> Runner<D> {
+    /// Recovers a lost device, then updates the context and the demo once
+    /// the next frame is due.
+    fn about_to_wait(&mut self, target: &Target) {
+        if self.context.is_device_lost() {
+            let recreated = self.context.recreate_device(&self.window).block_on();
+            if let Err(e) = recreated.and_then(|()| self.demo.reinit(&mut self.context)) {
+                self.exit_error = Some(e);
+                target.exit();
+                return;
             }
+            self.demo.resize(&self.context);
+            self.context.request_redraw();
+        }
 
-            Event::DeviceEvent { ref event, .. } if main_window_focused => match event {
-                DeviceEvent::Button {
-                    #[cfg(target_os = "macos")]
-                        button: 0,
-                    #[cfg(not(target_os = "macos"))]
-                        button: 1,
-
-                    state: statee,
-                } => {
-                    let is_pressed = *statee == ElementState::Pressed;
-                    mouse_dragged = is_pressed;
-                }
-                DeviceEvent::MouseWheel { delta, .. } => {
-                    let scroll_amount = -match delta {
-                        MouseScrollDelta::LineDelta(_, scroll) => scroll * 1.0,
-                        MouseScrollDelta::PixelDelta(PhysicalPosition { y: scroll, .. }) => {
-                            *scroll as f32
-                        }
-                    };
-                    context.camera.add_zoom(scroll_amount * zoom_speed);
-                }
-                DeviceEvent::MouseMotion { delta } => {
-                    if mouse_dragged {
-                        context.camera.add_yaw(-delta.0 as f32 * rotate_speed);
-                        context.camera.add_pitch(delta.1 as f32 * rotate_speed);
-                    }
+        let timing = if self.replay.is_some() {
+            self.replay_tick(target)
+        } else {
+            self.live_timing(target)
+        };
+        let Some((timing, replayed_redraw)) = timing else {
+            return;
+        };
+        self.last_update = Instant::now();
+
+        handle_hotkeys(&mut self.context, self.replay.is_some());
+        self.context.update_camera(timing.dt);
+        self.context.update(&timing);
+        self.demo.update(&mut self.context);
+        self.context.input_mut().end_frame();
+        let redraw = self.context.take_redraw_request();
+        let written = self
+            .recorder
+            .as_mut()
+            .map(|recorder| recorder.tick(&self.context, timing, redraw));
+        if let Some(Err(e)) = written {
+            eprintln!("Failed to write the recording, stopping it: {e}");
+            self.recorder = None;
+        }
+        if replayed_redraw.unwrap_or(redraw) {
+            self.window.request_redraw();
+        }
+    }
+
+    /// Feeds the next recorded tick, exits once the replay is over.
+    fn replay_tick(&mut self, target: &Target) -> Option<(FrameTiming, Option<bool>)> {
+        let Some(tick) = self.replay.as_mut().and_then(Replay::next_tick) else {
+            eprintln!("Replay finished");
+            target.exit();
+            return None;
+        };
+        for event in &tick.events {
+            if let InputEvent::Resized { width, height } = *event {
+                let _ = self
+                    .window
+                    .request_inner_size(PhysicalSize::new(width, height));
+            } else {
+                self.input(event);
+            }
+        }
+        if self.context.set_replayed_resolution(tick.backbuffer_size) {
+            self.demo.resize(&self.context);
+        }
+        // Recorded ticks play back as fast as they render
+        target.set_control_flow(ControlFlow::Poll);
+        Some((tick.timing, Some(tick.redraw)))
+    }
+
+    /// Timing of the next frame from the clock, `None` while the frame rate
+    /// cap holds it back.
+    fn live_timing(&self, target: &Target) -> Option<(FrameTiming, Option<bool>)> {
+        if let Some(min_frame_time) = self.context.min_frame_time() {
+            let next_frame = self.last_frame + min_frame_time;
+            if Instant::now() < next_frame {
+                target.set_control_flow(ControlFlow::WaitUntil(next_frame));
+                return None;
+            }
+        }
+        let timing = FrameTiming {
+            dt: self.last_update.elapsed().as_secs_f32(),
+            frame: self.frame_counter.frame_count,
+            time_delta: self.frame_counter.time_delta(),
+            unix_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0., |since| since.as_secs_f64()),
+        };
+        Some((timing, None))
+    }
+
+    fn window_event(&mut self, window_event: WindowEvent, target: &Target) {
+        // Live input is ignored while replaying
+        if self.replay.is_none() {
+            if let Some(event) = InputEvent::from_window_event(&window_event) {
+                self.record_input(&event);
+            }
+        }
+        if matches!(
+            window_event,
+            WindowEvent::Resized(_)
+                | WindowEvent::ScaleFactorChanged { .. }
+                | WindowEvent::Occluded(false)
+        ) {
+            self.context.request_redraw();
+        }
+
+        match window_event {
+            WindowEvent::Focused(focused) => self.main_window_focused = focused,
+
+            WindowEvent::CloseRequested
+            | WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        logical_key: Key::Named(NamedKey::Escape),
+                        ..
+                    },
+                ..
+            } => target.exit(),
+
+            WindowEvent::RedrawRequested => self.redraw(),
+
+            WindowEvent::Resized(PhysicalSize { width, height }) => {
+                if width != 0 && height != 0 {
+                    self.context.resize(width, height);
+                    self.demo.resize(&self.context);
                 }
-                _ => (),
-            },
+            }
 
             _ => {}
         }
-    }).map_err(|e| e.to_string())
+        if self.replay.is_none() {
+            self.demo.update_input(window_event);
+        }
+    }
+
+    fn device_event(&mut self, device_event: &DeviceEvent) {
+        if !self.main_window_focused || self.replay.is_some() {
+            return;
+        }
+        if let Some(event) = InputEvent::from_device_event(device_event) {
+            self.record_input(&event);
+        }
+    }
+
+    fn redraw(&mut self) {
+        let frame_time = self.last_frame.elapsed();
+        self.last_frame = Instant::now();
+        self.frame_counter.record();
+
+        // Frame times would make replays diverge
+        if self.replay.is_none() && self.context.update_dynamic_resolution(frame_time) {
+            self.demo.resize(&self.context);
+        }
+
+        self.context.begin_frame_timing();
+        self.demo.render(&self.context);
+        self.context.end_frame_timing();
+
+        match self.context.render() {
+            Ok(_) => {}
+            Err(wgpu::SurfaceError::Lost) => {
+                self.context.resize(self.context.width, self.context.height);
+                self.window.request_redraw();
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                self.context.mark_device_lost();
+                self.window.request_redraw();
+            }
+            Err(e) => {
+                eprintln!("{:?}", e);
+                self.window.request_redraw();
+            }
+        }
+    }
+
+    /// Writes live input to the recording before handling it.
+    fn record_input(&mut self, event: &InputEvent) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.event(*event);
+        }
+        self.input(event);
+    }
+
+    fn input(&mut self, event: &InputEvent) {
+        if handle_input(&mut self.context, event) {
+            self.demo.resize(&self.context);
+        }
+    }
+}
+
+/// Routes input to [`Context`] and the built-in hotkeys, returns `true` when
+/// the backbuffer was resized.
+fn handle_input(context: &mut Context, event: &InputEvent) -> bool {
+    let window_size = PhysicalSize::new(context.width, context.height);
+    context.input_mut().update(event, window_size);
+    let resized = context.camera_input(event);
+    if event.requests_redraw() {
+        context.request_redraw();
+    }
+
+    resized
+}
+
+/// Built-in hotkeys, actions of the [`InputMap`] checked once per frame.
+fn handle_hotkeys(context: &mut Context, replaying: bool) {
+    if context.input().just_pressed(Input::NEXT_CAMERA_MODE) {
+        // Custom controllers stay in place
+        if let Some(mode) = context.camera_mode() {
+            context.set_camera_mode(mode.next());
+        }
+    }
+    let actions = Input::STORE_BOOKMARK
+        .into_iter()
+        .zip(Input::RECALL_BOOKMARK);
+    for (slot, (store, recall)) in (0..).zip(actions) {
+        let store = context.input().just_pressed(store);
+        let recall = context.input().just_pressed(recall);
+        // Storing takes precedence, recall bindings usually share its key
+        match (store, recall, replaying) {
+            // Replays only see the bookmarks of the recording
+            (true, _, true) => {
+                let view = CameraView::from_camera(&context.camera);
+                context.bookmarks_mut().store_slot(slot, view);
+            }
+            (true, _, false) => {
+                if let Err(e) = context.store_bookmark_slot(slot) {
+                    eprintln!("Failed to save camera bookmarks: {e}");
+                }
+            }
+            (false, true, _) => {
+                context.recall_bookmark_slot(slot);
+            }
+            (false, false, _) => {}
+        }
+    }
 }
@@ -0,0 +1,765 @@
+use crate::{Context, Error, FeedbackTexture, InputUniform, NonZeroSized};
+
+use bytemuck::{Pod, Zeroable};
+use pollster::FutureExt;
+use wgpu::util::DeviceExt;
+
+use std::path::Path;
+
+/// Offscreen pass of a [`Shadertoy`], buffers render in order before the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShadertoyBuffer {
+    A,
+    B,
+    C,
+    D,
+}
+
+impl ShadertoyBuffer {
+    pub const ALL: [Self; 4] = [Self::A, Self::B, Self::C, Self::D];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::A => "Buffer A",
+            Self::B => "Buffer B",
+            Self::C => "Buffer C",
+            Self::D => "Buffer D",
+        }
+    }
+}
+
+/// What a pass reads through one of `iChannel0` to `iChannel3`.
+#[allow(variant_size_differences)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelInput {
+    /// Transparent black.
+    #[default]
+    Empty,
+    /// Output of a buffer, from this frame if it renders before the reading
+    /// pass and from the previous frame otherwise. A buffer reading itself
+    /// gets its last frame back.
+    Buffer(ShadertoyBuffer),
+    /// Keyboard texture of [`InputBinding`](crate::InputBinding), sample it
+    /// with `texelFetch(iChannel0, ivec2(key_code, row), 0).x`.
+    Keyboard,
+    /// The n-th texture added with [`ShadertoyDesc::with_texture`].
+    Texture(usize),
+}
+
+/// Input of a channel and how it's sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Channel {
+    pub input: ChannelInput,
+    /// Used for magnification, minification and between mip levels.
+    pub filter: wgpu::FilterMode,
+    pub address_mode: wgpu::AddressMode,
+}
+
+impl Default for Channel {
+    fn default() -> Self {
+        Self::new(ChannelInput::Empty)
+    }
+}
+
+impl From<ChannelInput> for Channel {
+    fn from(input: ChannelInput) -> Self {
+        Self::new(input)
+    }
+}
+
+impl Channel {
+    /// Linear filtering clamped to the edge, the keyboard is read unfiltered.
+    pub fn new(input: ChannelInput) -> Self {
+        let filter = match input {
+            ChannelInput::Keyboard => wgpu::FilterMode::Nearest,
+            _ => wgpu::FilterMode::Linear,
+        };
+        Self {
+            input,
+            filter,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+        }
+    }
+
+    pub fn buffer(buffer: ShadertoyBuffer) -> Self {
+        Self::new(ChannelInput::Buffer(buffer))
+    }
+
+    pub fn keyboard() -> Self {
+        Self::new(ChannelInput::Keyboard)
+    }
+
+    pub fn texture(index: usize) -> Self {
+        Self::new(ChannelInput::Texture(index))
+    }
+
+    pub fn with_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_address_mode(mut self, address_mode: wgpu::AddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    fn create_sampler(&self, device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadertoy Channel Sampler"),
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            address_mode_w: self.address_mode,
+            mag_filter: self.filter,
+            min_filter: self.filter,
+            mipmap_filter: self.filter,
+            ..Default::default()
+        })
+    }
+}
+
+/// GLSL source of a pass defining `mainImage`, as pasted from Shadertoy,
+/// and its channels.
+#[derive(Debug, Clone, Default)]
+pub struct ShadertoyPass {
+    pub source: String,
+    pub channels: [Channel; 4],
+}
+
+impl ShadertoyPass {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            channels: Default::default(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(Self::new(std::fs::read_to_string(path)?))
+    }
+
+    /// Binds `channel` to `iChannel{index}`, `index` has to be below 4.
+    pub fn with_channel(mut self, index: usize, channel: impl Into<Channel>) -> Self {
+        self.channels[index] = channel.into();
+        self
+    }
+}
+
+/// Passes and textures of a [`Shadertoy`].
+#[derive(Debug, Default)]
+pub struct ShadertoyDesc {
+    /// Code prepended to every pass, Shadertoy's Common tab.
+    pub common: String,
+    pub buffers: [Option<ShadertoyPass>; 4],
+    pub image: ShadertoyPass,
+    textures: Vec<ChannelTexture>,
+}
+
+impl ShadertoyDesc {
+    pub fn new(image: ShadertoyPass) -> Self {
+        Self {
+            image,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_common(mut self, common: impl Into<String>) -> Self {
+        self.common = common.into();
+        self
+    }
+
+    pub fn with_buffer(mut self, buffer: ShadertoyBuffer, pass: ShadertoyPass) -> Self {
+        self.buffers[buffer.index()] = Some(pass);
+        self
+    }
+
+    /// Adds a 2D texture channels read with [`ChannelInput::Texture`], indexed
+    /// in the order of the calls.
+    ///
+    /// The texture has to be filterable. Shadertoy flips images so that the
+    /// first row is the bottom one, load them with
+    /// [`TextureOptions::flip_y`](crate::TextureOptions::flip_y) to get the same result.
+    pub fn with_texture(mut self, texture: &wgpu::Texture) -> Self {
+        self.textures.push(ChannelTexture {
+            view: texture.create_view(&Default::default()),
+            size: (texture.width(), texture.height()),
+        });
+        self
+    }
+}
+
+#[derive(Debug)]
+struct ChannelTexture {
+    view: wgpu::TextureView,
+    size: (u32, u32),
+}
+
+/// Shadertoy inputs with the std140 layout of the uniform block in
+/// `shadertoy_prelude.glsl`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct ShadertoyUniform {
+    resolution: [f32; 3],
+    time: f32,
+    mouse: [f32; 4],
+    /// Year, month from 0, day and seconds since midnight in UTC.
+    date: [f32; 4],
+    /// Array elements are padded to 16 bytes.
+    channel_resolution: [[f32; 4]; 4],
+    channel_time: [[f32; 4]; 4],
+    time_delta: f32,
+    frame_rate: f32,
+    frame: i32,
+    sample_rate: f32,
+}
+
+struct Pass {
+    label: &'static str,
+    /// `None` for the image.
+    target: Option<ShadertoyBuffer>,
+    channels: [Channel; 4],
+    samplers: [wgpu::Sampler; 4],
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    /// Channels of this frame and the next, the buffers alternate between
+    /// the two. Created along with the buffers.
+    channel_bind_groups: Vec<wgpu::BindGroup>,
+}
+
+/// Runs Shadertoy-style multipass `mainImage` shaders into
+/// [`Context::render_backbuffer`].
+///
+/// Each pass is GLSL as written on Shadertoy, with `iResolution`, `iTime`,
+/// `iTimeDelta`, `iFrameRate`, `iFrame`, `iMouse`, `iDate`,
+/// `iChannelResolution`, `iChannelTime` and `iChannel0` to `iChannel3`
+/// declared. Buffers are `Rgba16Float` targets of the render resolution,
+/// cleared whenever it changes. The image is written as display values and
+/// converted to linear, so it still goes through the tone mapping of the
+/// present pass. `iDate` is [`Context::unix_time`] in UTC. Everything comes
+/// from [`Context`] and replays like the rest of the frame.
+///
+/// The GLSL front end doesn't accept channels as function arguments and
+/// only knows 2D textures, cube maps and volumes have to be ported by hand.
+pub struct Shadertoy {
+    passes: Vec<Pass>,
+    textures: Vec<ChannelTexture>,
+    keyboard: wgpu::TextureView,
+    empty: wgpu::TextureView,
+    channel_layout: wgpu::BindGroupLayout,
+    buffers: [Option<FeedbackTexture>; 4],
+    /// Resolution of the buffers, zero before they exist.
+    size: (u32, u32),
+    frame: u32,
+    start_time: Option<f32>,
+    /// [`Context`] time of the previous frame, in seconds like `iTime`.
+    last_time: Option<f32>,
+    mouse: [f32; 4],
+    mouse_held: bool,
+}
+
+impl Shadertoy {
+    const SAMPLE_RATE: f32 = 44100.;
+    const KEYBOARD_SIZE: [f32; 4] = [256., 3., 1., 0.];
+
+    const UNIFORM_DESC: wgpu::BindGroupLayoutDescriptor<'static> =
+        wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadertoy Uniform Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: Some(ShadertoyUniform::SIZE),
+                },
+                count: None,
+            }],
+        };
+
+    const CHANNEL_DESC: wgpu::BindGroupLayoutDescriptor<'static> =
+        wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadertoy Channel Bind Group Layout"),
+            entries: &[
+                channel_texture_entry(0),
+                channel_sampler_entry(1),
+                channel_texture_entry(2),
+                channel_sampler_entry(3),
+                channel_texture_entry(4),
+                channel_sampler_entry(5),
+                channel_texture_entry(6),
+                channel_sampler_entry(7),
+            ],
+        };
+
+    /// Compiles every pass, shader errors are returned instead of raised
+    /// on the device.
+    pub fn new(ctx: &Context, desc: ShadertoyDesc) -> Result<Self, Error> {
+        let device = &ctx.device;
+        let vertex = device.create_shader_module(wgpu::include_wgsl!("../shaders/shadertoy.wgsl"));
+        let uniform_layout = device.create_bind_group_layout(&Self::UNIFORM_DESC);
+        let channel_layout = device.create_bind_group_layout(&Self::CHANNEL_DESC);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadertoy Pass Layout"),
+            bind_group_layouts: &[&uniform_layout, &channel_layout],
+            push_constant_ranges: &[],
+        });
+
+        let ShadertoyDesc {
+            common,
+            buffers,
+            image,
+            textures,
+        } = desc;
+        let defined: Vec<_> = ShadertoyBuffer::ALL
+            .into_iter()
+            .filter(|buffer| buffers[buffer.index()].is_some())
+            .collect();
+        let targets = ShadertoyBuffer::ALL
+            .into_iter()
+            .zip(buffers)
+            .filter_map(|(buffer, pass)| Some((Some(buffer), pass?)));
+        let mut passes = Vec::new();
+        for (target, pass) in targets.chain([(None, image)]) {
+            let label = target.map_or("Image", ShadertoyBuffer::label);
+            for (index, channel) in pass.channels.iter().enumerate() {
+                match channel.input {
+                    ChannelInput::Buffer(buffer) if !defined.contains(&buffer) => {
+                        eprintln!(
+                            "{label}: iChannel{index} reads {} which has no pass",
+                            buffer.label()
+                        );
+                    }
+                    ChannelInput::Texture(texture) if texture >= textures.len() => {
+                        eprintln!("{label}: iChannel{index} reads missing texture {texture}");
+                    }
+                    _ => {}
+                }
+            }
+
+            let pipeline = create_pipeline(
+                device,
+                &pipeline_layout,
+                &vertex,
+                label,
+                &format!("{common}\n{}", pass.source),
+                target.is_none(),
+            )?;
+            let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Shadertoy Uniform"),
+                contents: bytemuck::bytes_of(&ShadertoyUniform::zeroed()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Shadertoy Uniform Bind Group"),
+                layout: &uniform_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+            });
+            passes.push(Pass {
+                label,
+                target,
+                channels: pass.channels,
+                samplers: pass.channels.map(|channel| channel.create_sampler(device)),
+                pipeline,
+                uniform_buffer,
+                uniform_bind_group,
+                channel_bind_groups: Vec::new(),
+            });
+        }
+
+        let empty = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("Shadertoy Empty Channel"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&Default::default());
+
+        Ok(Self {
+            passes,
+            textures,
+            keyboard: ctx.input_binding.keyboard_view(),
+            empty,
+            channel_layout,
+            buffers: Default::default(),
+            size: (0, 0),
+            frame: 0,
+            start_time: None,
+            last_time: None,
+            mouse: [0.; 4],
+            mouse_held: false,
+        })
+    }
+
+    /// Replaces every pass, keeping time, frame and the contents of the
+    /// buffers if the new passes render to the same ones.
+    ///
+    /// Nothing changes when a pass fails to compile.
+    pub fn reload(&mut self, ctx: &Context, desc: ShadertoyDesc) -> Result<(), Error> {
+        let mut reloaded = Self::new(ctx, desc)?;
+        reloaded.frame = self.frame;
+        reloaded.start_time = self.start_time;
+        reloaded.last_time = self.last_time;
+        reloaded.mouse = self.mouse;
+        reloaded.mouse_held = self.mouse_held;
+        let targets = |toy: &Self| {
+            toy.passes
+                .iter()
+                .map(|pass| pass.target)
+                .collect::<Vec<_>>()
+        };
+        if targets(self) == targets(&reloaded) {
+            reloaded.buffers = std::mem::take(&mut self.buffers);
+            reloaded.size = self.size;
+            reloaded.create_bind_groups(&ctx.device);
+        }
+        *self = reloaded;
+        Ok(())
+    }
+
+    /// Clears the buffers and restarts time and frame count from zero.
+    pub fn reset(&mut self) {
+        self.buffers = Default::default();
+        self.size = (0, 0);
+        self.frame = 0;
+        self.start_time = None;
+        self.last_time = None;
+    }
+
+    /// Value of `iFrame` for the next frame.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    pub fn render(&mut self, ctx: &Context) {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Shadertoy Encoder"),
+            });
+        self.record(ctx, &mut encoder);
+        ctx.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Records every buffer and then the image into `encoder`.
+    pub fn record(&mut self, ctx: &Context, encoder: &mut wgpu::CommandEncoder) {
+        let size = ctx.render_backbuffer.size();
+        if size != self.size {
+            self.create_buffers(&ctx.device, size);
+        }
+        self.update_mouse(ctx.input_binding.uniform());
+
+        let global = &ctx.global_uniform;
+        let time = global.time - *self.start_time.get_or_insert(global.time);
+        let time_delta = self
+            .last_time
+            .map_or(0., |last_time| (global.time - last_time).max(0.));
+        self.last_time = Some(global.time);
+        let base = ShadertoyUniform {
+            resolution: [size.0 as f32, size.1 as f32, 1.],
+            time,
+            mouse: self.mouse,
+            date: utc_date(ctx.unix_time()),
+            channel_resolution: [[0.; 4]; 4],
+            channel_time: [[time, 0., 0., 0.]; 4],
+            time_delta,
+            frame_rate: if time_delta > 0. { 1. / time_delta } else { 0. },
+            frame: self.frame as i32,
+            sample_rate: Self::SAMPLE_RATE,
+        };
+
+        for pass in &mut self.passes {
+            let uniform = ShadertoyUniform {
+                channel_resolution: pass.channels.map(|channel| {
+                    channel_resolution(channel.input, &self.textures, &self.buffers, self.size)
+                }),
+                ..base
+            };
+            ctx.queue
+                .write_buffer(&pass.uniform_buffer, 0, bytemuck::bytes_of(&uniform));
+
+            let view = match pass.target {
+                Some(buffer) => match &self.buffers[buffer.index()] {
+                    Some(feedback) => feedback.write_view(),
+                    None => continue,
+                },
+                None => &ctx.render_backbuffer.texture_view,
+            };
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(pass.label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                ..Default::default()
+            });
+            rpass.set_pipeline(&pass.pipeline);
+            rpass.set_bind_group(0, &pass.uniform_bind_group, &[]);
+            rpass.set_bind_group(1, &pass.channel_bind_groups[0], &[]);
+            rpass.draw(0..3, 0..1);
+            drop(rpass);
+            pass.channel_bind_groups.swap(0, 1);
+        }
+
+        for feedback in self.buffers.iter_mut().flatten() {
+            feedback.swap();
+        }
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// `iMouse` keeps the last position the button was held at in `xy`, the
+    /// click position in `zw`. `z` turns negative on release, `w` after the
+    /// frame of the click.
+    fn update_mouse(&mut self, input: &InputUniform) {
+        let held = input.buttons & 1 != 0;
+        if held {
+            self.mouse[0] = input.cursor[0];
+            self.mouse[1] = input.cursor[1];
+        }
+        let clicked = held && !self.mouse_held;
+        let [x, y] = input.click;
+        self.mouse[2] = if held { x } else { -x };
+        self.mouse[3] = if clicked { y } else { -y };
+        self.mouse_held = held;
+    }
+
+    fn create_buffers(&mut self, device: &wgpu::Device, size: (u32, u32)) {
+        for pass in &self.passes {
+            if let Some(buffer) = pass.target {
+                self.buffers[buffer.index()] = Some(FeedbackTexture::new_2d(device, size));
+            }
+        }
+        self.size = size;
+        self.create_bind_groups(device);
+    }
+
+    /// Bind groups of the current state of the buffers and of the one after
+    /// the next swap, which the second swap here restores.
+    fn create_bind_groups(&mut self, device: &wgpu::Device) {
+        let mut bind_groups: Vec<Vec<_>> = self.passes.iter().map(|_| Vec::new()).collect();
+        for _ in 0..2 {
+            for (position, bind_groups) in bind_groups.iter_mut().enumerate() {
+                bind_groups.push(self.create_channel_bind_group(device, position));
+            }
+            for feedback in self.buffers.iter_mut().flatten() {
+                feedback.swap();
+            }
+        }
+        for (pass, bind_groups) in self.passes.iter_mut().zip(bind_groups) {
+            pass.channel_bind_groups = bind_groups;
+        }
+    }
+
+    fn create_channel_bind_group(&self, device: &wgpu::Device, position: usize) -> wgpu::BindGroup {
+        let pass = &self.passes[position];
+        let views = pass
+            .channels
+            .map(|channel| self.channel_view(channel.input, position));
+        let entries: Vec<_> = views
+            .iter()
+            .zip(&pass.samplers)
+            .enumerate()
+            .flat_map(|(index, (view, sampler))| {
+                [
+                    wgpu::BindGroupEntry {
+                        binding: 2 * index as u32,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2 * index as u32 + 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                ]
+            })
+            .collect();
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadertoy Channel Bind Group"),
+            layout: &self.channel_layout,
+            entries: &entries,
+        })
+    }
+
+    /// View a pass at `position` reads in the current state of the buffers.
+    fn channel_view(&self, input: ChannelInput, position: usize) -> &wgpu::TextureView {
+        match input {
+            ChannelInput::Empty => &self.empty,
+            ChannelInput::Keyboard => &self.keyboard,
+            ChannelInput::Texture(index) => self
+                .textures
+                .get(index)
+                .map_or(&self.empty, |texture| &texture.view),
+            ChannelInput::Buffer(buffer) => {
+                let written = self
+                    .passes
+                    .iter()
+                    .position(|pass| pass.target == Some(buffer));
+                match (written, &self.buffers[buffer.index()]) {
+                    // Earlier passes already wrote this frame
+                    (Some(written), Some(feedback)) if written < position => feedback.write_view(),
+                    (Some(_), Some(feedback)) => feedback.read_view(),
+                    _ => &self.empty,
+                }
+            }
+        }
+    }
+}
+
+/// `iChannelResolution` of `input`, a free function so passes can be
+/// borrowed mutably meanwhile.
+fn channel_resolution(
+    input: ChannelInput,
+    textures: &[ChannelTexture],
+    buffers: &[Option<FeedbackTexture>; 4],
+    buffer_size: (u32, u32),
+) -> [f32; 4] {
+    let (width, height) = match input {
+        ChannelInput::Empty => return [0.; 4],
+        ChannelInput::Keyboard => return Shadertoy::KEYBOARD_SIZE,
+        ChannelInput::Texture(index) => match textures.get(index) {
+            Some(texture) => texture.size,
+            None => return [0.; 4],
+        },
+        ChannelInput::Buffer(buffer) if buffers[buffer.index()].is_some() => buffer_size,
+        ChannelInput::Buffer(_) => return [0.; 4],
+    };
+    [width as f32, height as f32, 1., 0.]
+}
+
+const fn channel_texture_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+const fn channel_sampler_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+        count: None,
+    }
+}
+
+/// Fragment pipeline of a pass, `source` is the common code followed by the
+/// pass.
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    vertex: &wgpu::ShaderModule,
+    label: &str,
+    source: &str,
+    image: bool,
+) -> Result<wgpu::RenderPipeline, Error> {
+    let shader = format!(
+        "{}{source}\n{}",
+        include_str!("../shaders/shadertoy_prelude.glsl"),
+        include_str!("../shaders/shadertoy_main.glsl"),
+    );
+    let mut defines = wgpu::naga::FastHashMap::default();
+    if image {
+        defines.insert("VOKSELIS_IMAGE_PASS".to_owned(), "1".to_owned());
+    }
+
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some(label),
+        source: wgpu::ShaderSource::Glsl {
+            shader: shader.into(),
+            stage: wgpu::naga::ShaderStage::Fragment,
+            defines,
+        },
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: vertex,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &module,
+            entry_point: "main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: FeedbackTexture::FORMAT,
+                blend: None,
+                write_mask: Default::default(),
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+    match device.pop_error_scope().block_on() {
+        Some(error) => Err(Error::Shader {
+            label: label.to_owned(),
+            message: error.to_string(),
+        }),
+        None => Ok(pipeline),
+    }
+}
+
+/// `iDate` at `seconds` since the Unix epoch.
+fn utc_date(seconds: f64) -> [f32; 4] {
+    let days = (seconds / 86400.).floor() as i64;
+    let seconds_of_day = seconds - days as f64 * 86400.;
+
+    // Proleptic Gregorian calendar from days since 1970-01-01, eras start in March
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 2
+    } else {
+        month_from_march - 10
+    };
+    let year = era * 400 + year_of_era + i64::from(month < 2);
+    [year as f32, month as f32, day as f32, seconds_of_day as f32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utc_date_of_fixed_timestamps() {
+        assert_eq!(utc_date(0.), [1970., 0., 1., 0.]);
+        assert_eq!(utc_date(1_700_000_000.5), [2023., 10., 14., 80_000.5]);
+        // Leap day and the first day of a March based era
+        assert_eq!(utc_date(1_709_164_800.), [2024., 1., 29., 0.]);
+        assert_eq!(utc_date(951_868_800.), [2000., 2., 1., 0.]);
+        assert_eq!(utc_date(951_868_799.), [2000., 1., 29., 86_399.]);
+    }
+}
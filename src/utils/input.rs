@@ -1,112 +1,706 @@
-use crate::context::Uniform;
+use crate::{context::Uniform, Error};
 
+use serde::{Deserialize, Serialize};
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{ElementState, KeyEvent, WindowEvent},
-    keyboard::{KeyCode, PhysicalKey},
-    window::Window,
+    event::{DeviceEvent, ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
 };
 
-#[derive(Debug, Default)]
-pub struct Input {
-    pub up_pressed: bool,
-    pub down_pressed: bool,
-    pub right_pressed: bool,
-    pub left_pressed: bool,
-    pub slash_pressed: bool,
-    pub right_shift_pressed: bool,
-    pub enter_pressed: bool,
-    pub space_pressed: bool,
-    pub left_mouse_pressed: bool,
-    pub mouse_position: [f32; 2],
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+};
+
+/// Wheel motion in lines or pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollDelta {
+    Line { x: f32, y: f32 },
+    Pixel { x: f64, y: f64 },
 }
 
-impl Input {
-    pub fn new() -> Self {
-        Default::default()
+impl ScrollDelta {
+    /// Vertical motion in lines, a line is 100 pixels.
+    pub fn lines(self) -> f32 {
+        match self {
+            Self::Line { y, .. } => y,
+            Self::Pixel { y, .. } => y as f32 / 100.,
+        }
     }
+}
 
-    pub fn update(&mut self, event: &WindowEvent, window: &Window) -> bool {
-        match event {
+impl From<MouseScrollDelta> for ScrollDelta {
+    fn from(delta: MouseScrollDelta) -> Self {
+        match delta {
+            MouseScrollDelta::LineDelta(x, y) => Self::Line { x, y },
+            MouseScrollDelta::PixelDelta(PhysicalPosition { x, y }) => Self::Pixel { x, y },
+        }
+    }
+}
+
+/// Window and device input [`run`](crate::run) acts on, in a form that can
+/// be recorded and replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputEvent {
+    Key {
+        code: KeyCode,
+        pressed: bool,
+        repeat: bool,
+    },
+    Modifiers(ModifiersState),
+    MouseButton {
+        button: MouseButton,
+        pressed: bool,
+    },
+    /// Cursor position in physical pixels from the top left corner.
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    CursorLeft,
+    MouseWheel(ScrollDelta),
+    Focused(bool),
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    /// Raw mouse motion, only while the window is focused.
+    MouseMotion {
+        dx: f64,
+        dy: f64,
+    },
+    /// Raw mouse button, ids are platform specific.
+    DeviceButton {
+        button: u32,
+        pressed: bool,
+    },
+    DeviceWheel(ScrollDelta),
+}
+
+impl InputEvent {
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        Some(match event {
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
-                        physical_key,
+                        physical_key: PhysicalKey::Code(code),
                         state,
+                        repeat,
                         ..
                     },
                 ..
-            } => {
-                let pressed = state == &ElementState::Pressed;
-                match physical_key {
-                    PhysicalKey::Code(KeyCode::ArrowUp) => {
-                        self.up_pressed = pressed;
-                    }
-                    PhysicalKey::Code(KeyCode::ArrowDown) => {
-                        self.down_pressed = pressed;
-                    }
-                    PhysicalKey::Code(KeyCode::ArrowLeft) => {
-                        self.left_pressed = pressed;
-                    }
-                    PhysicalKey::Code(KeyCode::ArrowRight) => {
-                        self.right_pressed = pressed;
-                    }
-                    PhysicalKey::Code(KeyCode::Slash) => {
-                        self.slash_pressed = pressed;
-                    }
-                    PhysicalKey::Code(KeyCode::ShiftRight) => {
-                        self.right_shift_pressed = pressed;
-                    }
-                    PhysicalKey::Code(KeyCode::Enter) => {
-                        self.enter_pressed = pressed;
-                    }
-                    PhysicalKey::Code(KeyCode::Space) => {
-                        self.space_pressed = pressed;
-                    }
-                    _ => return false,
-                };
-            }
+            } => Self::Key {
+                code: *code,
+                pressed: *state == ElementState::Pressed,
+                repeat: *repeat,
+            },
+            WindowEvent::ModifiersChanged(modifiers) => Self::Modifiers(modifiers.state()),
+            WindowEvent::MouseInput { button, state, .. } => Self::MouseButton {
+                button: *button,
+                pressed: *state == ElementState::Pressed,
+            },
             WindowEvent::CursorMoved {
                 position: PhysicalPosition { x, y },
                 ..
-            } => {
-                let PhysicalSize { width, height } = window.inner_size();
-                let x = (*x as f32 / width as f32 - 0.5) * 2.;
-                let y = -(*y as f32 / height as f32 - 0.5) * 2.;
-                self.mouse_position = [x, y];
-            }
-            WindowEvent::MouseInput {
-                button: winit::event::MouseButton::Left,
-                state,
-                ..
-            } => self.left_mouse_pressed = matches!(state, ElementState::Pressed),
+            } => Self::CursorMoved { x: *x, y: *y },
+            WindowEvent::CursorLeft { .. } => Self::CursorLeft,
+            WindowEvent::MouseWheel { delta, .. } => Self::MouseWheel((*delta).into()),
+            WindowEvent::Focused(focused) => Self::Focused(*focused),
+            WindowEvent::Resized(PhysicalSize { width, height }) => Self::Resized {
+                width: *width,
+                height: *height,
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn from_device_event(event: &DeviceEvent) -> Option<Self> {
+        Some(match event {
+            DeviceEvent::MouseMotion { delta: (dx, dy) } => Self::MouseMotion { dx: *dx, dy: *dy },
+            DeviceEvent::Button { button, state } => Self::DeviceButton {
+                button: *button,
+                pressed: *state == ElementState::Pressed,
+            },
+            DeviceEvent::MouseWheel { delta } => Self::DeviceWheel((*delta).into()),
+            _ => return None,
+        })
+    }
+
+    /// Whether the event may change the image.
+    pub fn requests_redraw(&self) -> bool {
+        matches!(
+            self,
+            Self::Key { .. } | Self::MouseButton { .. } | Self::MouseWheel(_)
+        )
+    }
+}
+
+/// Key or mouse button a [`Binding`] reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
 
-            _ => { }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Modifier {
+    Shift,
+    Control,
+    Alt,
+    Super,
+}
+
+impl Modifier {
+    fn is_held(self, state: ModifiersState) -> bool {
+        match self {
+            Self::Shift => state.shift_key(),
+            Self::Control => state.control_key(),
+            Self::Alt => state.alt_key(),
+            Self::Super => state.super_key(),
         }
-        true
+    }
+}
+
+/// Trigger held together with the listed modifiers, e.g.
+/// `{ "key": "KeyS", "modifiers": ["control"] }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    #[serde(flatten)]
+    pub trigger: Trigger,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<Modifier>,
+}
+
+impl Binding {
+    pub fn key(code: KeyCode) -> Self {
+        Trigger::Key(code).into()
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Trigger::Mouse(button).into()
     }
 
-    pub fn process_position(&self, uniform: &mut Uniform) {
-        let dx = 0.01;
-        if self.left_pressed {
-            uniform.pos[0] -= dx;
+    pub fn with_modifier(mut self, modifier: Modifier) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+}
+
+impl From<Trigger> for Binding {
+    fn from(trigger: Trigger) -> Self {
+        Self {
+            trigger,
+            modifiers: Vec::new(),
+        }
+    }
+}
+
+/// Pair of bindings driving an axis to `scale` and `-scale`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AxisBinding {
+    pub positive: Binding,
+    pub negative: Binding,
+    #[serde(default = "AxisBinding::default_scale")]
+    pub scale: f32,
+}
+
+impl AxisBinding {
+    pub fn new(positive: impl Into<Binding>, negative: impl Into<Binding>) -> Self {
+        Self {
+            positive: positive.into(),
+            negative: negative.into(),
+            scale: Self::default_scale(),
         }
-        if self.right_pressed {
-            uniform.pos[0] += dx;
+    }
+
+    fn default_scale() -> f32 {
+        1.
+    }
+}
+
+/// Names of actions and axes with the inputs bound to them, read from a
+/// JSON file like
+///
+/// ```json
+/// {
+///   "actions": { "confirm": [{ "key": "Enter" }] },
+///   "axes": { "move_x": [{ "positive": { "key": "KeyD" }, "negative": { "key": "KeyA" } }] }
+/// }
+/// ```
+///
+/// Key names are the variants of `winit::keyboard::KeyCode`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    #[serde(default)]
+    pub actions: BTreeMap<String, Vec<Binding>>,
+    #[serde(default)]
+    pub axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl Default for InputMap {
+    /// Arrows, `/` and right `Shift` move [`Uniform::pos`], the left mouse
+    /// button sets [`Uniform::mouse_pressed`]. `Tab` cycles camera modes,
+    /// digits recall camera bookmarks and `Ctrl` + digit stores them. WASD
+    /// and Q/E fly the [`FlyController`](crate::FlyController), faster with
+    /// `Shift` and slower with `Ctrl`.
+    fn default() -> Self {
+        let key = |code| Binding::key(code);
+        let mut actions = BTreeMap::from([
+            (Input::CLICK.into(), vec![Binding::mouse(MouseButton::Left)]),
+            ("confirm".into(), vec![key(KeyCode::Enter)]),
+            ("toggle".into(), vec![key(KeyCode::Space)]),
+            (Input::NEXT_CAMERA_MODE.into(), vec![key(KeyCode::Tab)]),
+            (
+                Input::FLY_FAST.into(),
+                vec![key(KeyCode::ShiftLeft), key(KeyCode::ShiftRight)],
+            ),
+            (
+                Input::FLY_SLOW.into(),
+                vec![key(KeyCode::ControlLeft), key(KeyCode::ControlRight)],
+            ),
+        ]);
+        const DIGITS: [KeyCode; 10] = [
+            KeyCode::Digit0,
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+        for (slot, code) in DIGITS.into_iter().enumerate() {
+            actions.insert(Input::RECALL_BOOKMARK[slot].into(), vec![key(code)]);
+            actions.insert(
+                Input::STORE_BOOKMARK[slot].into(),
+                vec![key(code).with_modifier(Modifier::Control)],
+            );
         }
-        if self.down_pressed {
-            uniform.pos[1] -= dx;
+        Self {
+            actions,
+            axes: BTreeMap::from([
+                (
+                    Input::MOVE_X.into(),
+                    vec![AxisBinding::new(
+                        key(KeyCode::ArrowRight),
+                        key(KeyCode::ArrowLeft),
+                    )],
+                ),
+                (
+                    Input::MOVE_Y.into(),
+                    vec![AxisBinding::new(
+                        key(KeyCode::ArrowUp),
+                        key(KeyCode::ArrowDown),
+                    )],
+                ),
+                (
+                    Input::MOVE_Z.into(),
+                    vec![AxisBinding::new(
+                        key(KeyCode::ShiftRight),
+                        key(KeyCode::Slash),
+                    )],
+                ),
+                (
+                    Input::FLY_FORWARD.into(),
+                    vec![AxisBinding::new(key(KeyCode::KeyW), key(KeyCode::KeyS))],
+                ),
+                (
+                    Input::FLY_RIGHT.into(),
+                    vec![AxisBinding::new(key(KeyCode::KeyD), key(KeyCode::KeyA))],
+                ),
+                (
+                    Input::FLY_UP.into(),
+                    vec![AxisBinding::new(key(KeyCode::KeyE), key(KeyCode::KeyQ))],
+                ),
+            ]),
+        }
+    }
+}
+
+impl InputMap {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn bind_action(&mut self, action: impl Into<String>, binding: impl Into<Binding>) {
+        self.actions
+            .entry(action.into())
+            .or_default()
+            .push(binding.into());
+    }
+
+    pub fn bind_axis(&mut self, axis: impl Into<String>, binding: AxisBinding) {
+        self.axes.entry(axis.into()).or_default().push(binding);
+    }
+}
+
+/// State of the keyboard and mouse queried through an [`InputMap`].
+#[derive(Debug)]
+pub struct Input {
+    pub map: InputMap,
+    /// Units per second [`Uniform::pos`] moves along the `move_*` axes.
+    pub move_speed: f32,
+    pub mouse_position: [f32; 2],
+
+    held: HashSet<Trigger>,
+    pressed: HashSet<Trigger>,
+    toggled: HashSet<KeyCode>,
+    modifiers: ModifiersState,
+    cursor: [f32; 2],
+    click: [f32; 2],
+    wheel: [f32; 2],
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::with_map(InputMap::default())
+    }
+}
+
+impl Input {
+    /// Action setting [`Uniform::mouse_pressed`].
+    pub const CLICK: &'static str = "click";
+    /// Action switching to the next [`CameraMode`](crate::CameraMode) in [`run`](crate::run).
+    pub const NEXT_CAMERA_MODE: &'static str = "next_camera_mode";
+    /// Actions moving the camera to the bookmark in a slot in [`run`](crate::run).
+    pub const RECALL_BOOKMARK: [&'static str; 10] = [
+        "recall_bookmark_0",
+        "recall_bookmark_1",
+        "recall_bookmark_2",
+        "recall_bookmark_3",
+        "recall_bookmark_4",
+        "recall_bookmark_5",
+        "recall_bookmark_6",
+        "recall_bookmark_7",
+        "recall_bookmark_8",
+        "recall_bookmark_9",
+    ];
+    /// Actions bookmarking the current view in a slot in [`run`](crate::run).
+    pub const STORE_BOOKMARK: [&'static str; 10] = [
+        "store_bookmark_0",
+        "store_bookmark_1",
+        "store_bookmark_2",
+        "store_bookmark_3",
+        "store_bookmark_4",
+        "store_bookmark_5",
+        "store_bookmark_6",
+        "store_bookmark_7",
+        "store_bookmark_8",
+        "store_bookmark_9",
+    ];
+    /// Axes moving [`Uniform::pos`].
+    pub const MOVE_X: &'static str = "move_x";
+    pub const MOVE_Y: &'static str = "move_y";
+    pub const MOVE_Z: &'static str = "move_z";
+    /// Axes flying the [`FlyController`](crate::FlyController).
+    pub const FLY_FORWARD: &'static str = "fly_forward";
+    pub const FLY_RIGHT: &'static str = "fly_right";
+    pub const FLY_UP: &'static str = "fly_up";
+    /// Actions speeding the [`FlyController`](crate::FlyController) up and
+    /// slowing it down.
+    pub const FLY_FAST: &'static str = "fly_fast";
+    pub const FLY_SLOW: &'static str = "fly_slow";
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_map(map: InputMap) -> Self {
+        Self {
+            map,
+            move_speed: 0.6,
+            mouse_position: [0.; 2],
+
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            toggled: HashSet::new(),
+            modifiers: ModifiersState::empty(),
+            cursor: [0.; 2],
+            click: [0.; 2],
+            wheel: [0.; 2],
         }
-        if self.up_pressed {
-            uniform.pos[1] += dx;
+    }
+
+    /// Tracks `event`, `window_size` normalises the cursor position.
+    pub fn update(&mut self, event: &InputEvent, window_size: PhysicalSize<u32>) {
+        match *event {
+            InputEvent::Key { code, pressed, .. } => {
+                if self.set_held(Trigger::Key(code), pressed) && !self.toggled.remove(&code) {
+                    self.toggled.insert(code);
+                }
+            }
+            InputEvent::MouseButton { button, pressed } => {
+                if self.set_held(Trigger::Mouse(button), pressed) && button == MouseButton::Left {
+                    self.click = self.cursor;
+                }
+            }
+            InputEvent::MouseWheel(delta) => {
+                let (x, y) = match delta {
+                    ScrollDelta::Line { x, y } => (x, y),
+                    ScrollDelta::Pixel { x, y } => (x as f32 / 100., y as f32 / 100.),
+                };
+                self.wheel[0] += x;
+                self.wheel[1] += y;
+            }
+            InputEvent::Modifiers(modifiers) => self.modifiers = modifiers,
+            InputEvent::CursorMoved { x, y } => {
+                self.cursor = [x as f32, y as f32];
+                let PhysicalSize { width, height } = window_size;
+                let x = (x as f32 / width as f32 - 0.5) * 2.;
+                let y = -(y as f32 / height as f32 - 0.5) * 2.;
+                self.mouse_position = [x, y];
+            }
+            // Releases while unfocused never arrive
+            InputEvent::Focused(false) => {
+                self.held.clear();
+                self.modifiers = ModifiersState::empty();
+            }
+            _ => {}
         }
-        if self.slash_pressed {
-            uniform.pos[2] -= dx;
+    }
+
+    /// Returns `true` when `trigger` went down.
+    fn set_held(&mut self, trigger: Trigger, pressed: bool) -> bool {
+        if !pressed {
+            self.held.remove(&trigger);
+            return false;
         }
-        if self.right_shift_pressed {
-            uniform.pos[2] += dx;
+        let went_down = self.held.insert(trigger);
+        if went_down {
+            self.pressed.insert(trigger);
         }
-        uniform.mouse_pressed = self.left_mouse_pressed as _;
+        went_down
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    fn is_held(&self, binding: &Binding) -> bool {
+        self.held.contains(&binding.trigger)
+            && binding
+                .modifiers
+                .iter()
+                .all(|modifier| modifier.is_held(self.modifiers))
+    }
+
+    /// Whether a binding of `action` is held.
+    pub fn is_active(&self, action: &str) -> bool {
+        self.map
+            .actions
+            .get(action)
+            .is_some_and(|bindings| bindings.iter().any(|binding| self.is_held(binding)))
+    }
+
+    /// Whether `action` started since the last frame.
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.map.actions.get(action).is_some_and(|bindings| {
+            bindings
+                .iter()
+                .any(|binding| self.pressed.contains(&binding.trigger) && self.is_held(binding))
+        })
+    }
+
+    /// Sum of the held bindings of `axis`, each adding its `scale`. The sum
+    /// is clamped to the largest scale, so bindings held together don't
+    /// move faster than one.
+    pub fn axis(&self, axis: &str) -> f32 {
+        let Some(bindings) = self.map.axes.get(axis) else {
+            return 0.;
+        };
+        let max_scale = bindings
+            .iter()
+            .map(|binding| binding.scale.abs())
+            .fold(0., f32::max);
+        let value: f32 = bindings
+            .iter()
+            .map(|binding| {
+                let direction = self.is_held(&binding.positive) as i32 as f32
+                    - self.is_held(&binding.negative) as i32 as f32;
+                direction * binding.scale
+            })
+            .sum();
+        value.clamp(-max_scale, max_scale)
+    }
+
+    pub fn is_key_held(&self, code: KeyCode) -> bool {
+        self.held.contains(&Trigger::Key(code))
+    }
+
+    /// Whether `code` went down since the last frame.
+    pub fn is_key_pressed(&self, code: KeyCode) -> bool {
+        self.pressed.contains(&Trigger::Key(code))
+    }
+
+    /// Flips on every press of `code`, starts off.
+    pub fn is_key_toggled(&self, code: KeyCode) -> bool {
+        self.toggled.contains(&code)
+    }
+
+    pub fn is_mouse_held(&self, button: MouseButton) -> bool {
+        self.held.contains(&Trigger::Mouse(button))
+    }
+
+    pub(crate) fn held_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.held.iter().filter_map(|trigger| match trigger {
+            Trigger::Key(code) => Some(*code),
+            Trigger::Mouse(_) => None,
+        })
+    }
+
+    pub(crate) fn pressed_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.pressed.iter().filter_map(|trigger| match trigger {
+            Trigger::Key(code) => Some(*code),
+            Trigger::Mouse(_) => None,
+        })
+    }
+
+    pub(crate) fn toggled_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.toggled.iter().copied()
+    }
+
+    /// Cursor in physical pixels from the top left corner of the window.
+    pub fn cursor_position(&self) -> [f32; 2] {
+        self.cursor
+    }
+
+    /// Cursor position when the left button last went down, the origin of a drag.
+    pub fn click_position(&self) -> [f32; 2] {
+        self.click
+    }
+
+    /// Lines scrolled since the last frame, a line is 100 pixels.
+    pub fn wheel_delta(&self) -> [f32; 2] {
+        self.wheel
+    }
+
+    /// Forgets presses reported by [`Input::just_pressed`] and the scrolled
+    /// distance.
+    pub(crate) fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.wheel = [0.; 2];
+    }
+
+    /// Moves [`Uniform::pos`] over `dt` seconds and forwards the mouse.
+    pub fn process_position(&self, uniform: &mut Uniform, dt: f32) {
+        let step = self.move_speed * dt;
+        uniform.pos[0] += self.axis(Self::MOVE_X) * step;
+        uniform.pos[1] += self.axis(Self::MOVE_Y) * step;
+        uniform.pos[2] += self.axis(Self::MOVE_Z) * step;
+        uniform.mouse_pressed = self.is_active(Self::CLICK) as _;
         uniform.mouse = self.mouse_position;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: PhysicalSize<u32> = PhysicalSize::new(100, 100);
+
+    fn key(code: KeyCode, pressed: bool) -> InputEvent {
+        InputEvent::Key {
+            code,
+            pressed,
+            repeat: false,
+        }
+    }
+
+    #[test]
+    fn axis_follows_held_keys() {
+        let mut input = Input::new();
+        assert_eq!(input.axis(Input::MOVE_X), 0.);
+        input.update(&key(KeyCode::ArrowRight, true), SIZE);
+        assert_eq!(input.axis(Input::MOVE_X), 1.);
+        input.update(&key(KeyCode::ArrowLeft, true), SIZE);
+        assert_eq!(input.axis(Input::MOVE_X), 0.);
+        input.update(&key(KeyCode::ArrowRight, false), SIZE);
+        assert_eq!(input.axis(Input::MOVE_X), -1.);
+        assert_eq!(input.axis("missing"), 0.);
+    }
+
+    #[test]
+    fn axis_scale_is_not_clamped_to_one() {
+        let mut map = InputMap::default();
+        let mut fast = AxisBinding::new(Binding::key(KeyCode::KeyD), Binding::key(KeyCode::KeyA));
+        fast.scale = 3.;
+        map.bind_axis(Input::MOVE_X, fast);
+        let mut input = Input::with_map(map);
+
+        input.update(&key(KeyCode::KeyD, true), SIZE);
+        assert_eq!(input.axis(Input::MOVE_X), 3.);
+        // Held together they don't add up past the largest scale
+        input.update(&key(KeyCode::ArrowRight, true), SIZE);
+        assert_eq!(input.axis(Input::MOVE_X), 3.);
+        input.update(&key(KeyCode::KeyD, false), SIZE);
+        assert_eq!(input.axis(Input::MOVE_X), 1.);
+    }
+
+    #[test]
+    fn just_pressed_lasts_one_frame() {
+        let mut input = Input::new();
+        input.update(&key(KeyCode::Enter, true), SIZE);
+        assert!(input.just_pressed("confirm"));
+        assert!(input.is_active("confirm"));
+        // Repeats don't press again
+        input.update(&key(KeyCode::Enter, true), SIZE);
+        input.end_frame();
+        assert!(!input.just_pressed("confirm"));
+        assert!(input.is_active("confirm"));
+        assert!(!input.just_pressed("missing"));
+    }
+
+    #[test]
+    fn just_pressed_needs_modifiers() {
+        let mut input = Input::new();
+        input.update(&key(KeyCode::Digit3, true), SIZE);
+        assert!(input.just_pressed(Input::RECALL_BOOKMARK[3]));
+        assert!(!input.just_pressed(Input::STORE_BOOKMARK[3]));
+        input.update(&key(KeyCode::Digit3, false), SIZE);
+        input.end_frame();
+
+        input.update(&InputEvent::Modifiers(ModifiersState::CONTROL), SIZE);
+        input.update(&key(KeyCode::Digit3, true), SIZE);
+        assert!(input.just_pressed(Input::STORE_BOOKMARK[3]));
+    }
+
+    #[test]
+    fn input_map_json_round_trip() {
+        let mut map = InputMap::default();
+        map.bind_action(
+            "save",
+            Binding::key(KeyCode::KeyS).with_modifier(Modifier::Control),
+        );
+        map.bind_action("grab", Binding::mouse(MouseButton::Right));
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(serde_json::from_str::<InputMap>(&json).unwrap(), map);
+    }
+
+    #[test]
+    fn input_map_parses_documented_format() {
+        let json = r#"{
+            "actions": { "confirm": [{ "key": "Enter" }], "save": [{ "key": "KeyS", "modifiers": ["control"] }] },
+            "axes": { "move_x": [{ "positive": { "key": "KeyD" }, "negative": { "key": "KeyA" } }] }
+        }"#;
+        let map: InputMap = serde_json::from_str(json).unwrap();
+        assert_eq!(map.actions["confirm"], vec![Binding::key(KeyCode::Enter)]);
+        assert_eq!(
+            map.actions["save"],
+            vec![Binding::key(KeyCode::KeyS).with_modifier(Modifier::Control)]
+        );
+        assert_eq!(map.axes["move_x"][0].scale, 1.);
+    }
+}
@@ -1,5 +1,6 @@
 pub(crate) mod frame_counter;
 pub(crate) mod input;
+pub(crate) mod recording;
 
 use std::{
     collections::HashMap,
@@ -13,6 +14,18 @@ pub fn dispatch_optimal(len: u32, subgroup_size: u32) -> u32 {
     (len + padded_size) / subgroup_size
 }
 
+/// Radical inverse of `index` in `base`, the Halton sequence for prime bases.
+pub(crate) fn halton(base: u32, mut index: u32) -> f32 {
+    let mut result = 0.;
+    let mut fraction = 1. / base as f32;
+    while index > 0 {
+        result += (index % base) as f32 * fraction;
+        index /= base;
+        fraction /= base as f32;
+    }
+    result
+}
+
 pub trait NonZeroSized: Sized {
     const SIZE: NonZeroU64 = unsafe { NonZeroU64::new_unchecked(std::mem::size_of::<Self>() as _) };
 }
@@ -63,3 +76,24 @@ impl<K, V> Default for ContiniousHashMap<K, V> {
         Self(HashMap::new())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halton_radical_inverse() {
+        let base_2: Vec<f32> = (0..5).map(|index| halton(2, index)).collect();
+        assert_eq!(base_2, [0., 0.5, 0.25, 0.75, 0.125]);
+        let base_3: Vec<f32> = (1..4).map(|index| halton(3, index)).collect();
+        assert_eq!(base_3, [1. / 3., 2. / 3., 1. / 9.]);
+    }
+
+    #[test]
+    fn halton_stays_in_unit_interval() {
+        for index in 0..1024 {
+            let value = halton(3, index);
+            assert!((0. ..1.).contains(&value), "{value} at {index}");
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use crate::{CameraBookmarks, CameraMode, CameraView, Context, Error, InputEvent};
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/// Time step of one update of the event loop.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct FrameTiming {
+    /// Seconds since the previous update.
+    pub dt: f32,
+    /// Becomes [`Uniform::frame`](crate::Uniform::frame).
+    pub frame: u32,
+    /// Becomes [`Uniform::time_delta`](crate::Uniform::time_delta).
+    pub time_delta: f32,
+    /// Seconds since the Unix epoch, see [`Context::unix_time`].
+    #[serde(default)]
+    pub unix_time: f64,
+}
+
+/// Input received before an update, the update's timing and whether it
+/// drew a frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedTick {
+    #[serde(flatten)]
+    pub timing: FrameTiming,
+    pub redraw: bool,
+    /// Size of [`Context::render_backbuffer`] during the update, which
+    /// dynamic resolution changes while recording.
+    pub backbuffer_size: (u32, u32),
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<InputEvent>,
+}
+
+/// Session captured by [`run`](crate::run) with
+/// [`ContextOptions::record`](crate::ContextOptions::record).
+///
+/// Replaying feeds the same input with the same time steps, so every frame
+/// sees the same [`Uniform`](crate::Uniform) and [`Camera`](crate::Camera)
+/// as long as the window keeps the recorded size.
+///
+/// Stored as JSON lines, a [`RecordingHeader`] followed by one
+/// [`RecordedTick`] per line, so the file is usable up to the last tick
+/// when the recorded session crashes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub header: RecordingHeader,
+    pub ticks: Vec<RecordedTick>,
+}
+
+/// State of the [`Context`] when recording started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub window_size: (u32, u32),
+    pub camera: CameraView,
+    /// `None` when the demo installed its own controller.
+    pub camera_mode: Option<CameraMode>,
+    /// Bookmarks digits recall during the replay, which leaves the bookmark
+    /// file alone.
+    #[serde(default)]
+    pub bookmarks: CameraBookmarks,
+}
+
+impl Recording {
+    /// Reads a recording, a last line cut off mid-write is dropped.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut lines = BufReader::new(File::open(path)?).lines().peekable();
+        let header = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+        };
+        let mut ticks = Vec::new();
+        while let Some(line) = lines.next() {
+            match serde_json::from_str(&line?) {
+                Ok(tick) => ticks.push(tick),
+                Err(e) if lines.peek().is_none() && e.is_eof() => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Self { header, ticks })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = RecordingWriter::create(path, &self.header)?;
+        for tick in &self.ticks {
+            writer.write(tick)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends ticks to a recording file as they happen.
+struct RecordingWriter {
+    writer: BufWriter<File>,
+}
+
+impl RecordingWriter {
+    fn create(path: impl AsRef<Path>, header: &RecordingHeader) -> Result<Self, Error> {
+        let mut writer = Self {
+            writer: BufWriter::new(File::create(path)?),
+        };
+        writer.write(header)?;
+        Ok(writer)
+    }
+
+    /// Writes `line` and flushes it, so it survives a crash right after.
+    fn write(&mut self, line: &impl Serialize) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, line)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+pub(crate) struct Recorder {
+    writer: RecordingWriter,
+    events: Vec<InputEvent>,
+}
+
+impl Recorder {
+    /// Starts from the current state of `context`.
+    pub(crate) fn new(path: impl AsRef<Path>, context: &Context) -> Result<Self, Error> {
+        let header = RecordingHeader {
+            window_size: (context.width, context.height),
+            camera: CameraView::from_camera(&context.camera),
+            camera_mode: context.camera_mode(),
+            bookmarks: context.bookmarks().clone(),
+        };
+        Ok(Self {
+            writer: RecordingWriter::create(path, &header)?,
+            events: Vec::new(),
+        })
+    }
+
+    pub(crate) fn event(&mut self, event: InputEvent) {
+        self.events.push(event);
+    }
+
+    /// Writes the tick right away.
+    pub(crate) fn tick(
+        &mut self,
+        context: &Context,
+        timing: FrameTiming,
+        redraw: bool,
+    ) -> Result<(), Error> {
+        self.writer.write(&RecordedTick {
+            timing,
+            redraw,
+            backbuffer_size: context.render_backbuffer.size(),
+            events: std::mem::take(&mut self.events),
+        })
+    }
+}
+
+pub(crate) struct Replay {
+    ticks: std::vec::IntoIter<RecordedTick>,
+}
+
+impl Replay {
+    /// Puts `context` in the recorded initial state.
+    pub(crate) fn new(recording: Recording, context: &mut Context) -> Self {
+        let header = &recording.header;
+        if let Some(mode) = header.camera_mode {
+            context.set_camera_mode(mode);
+        }
+        header.camera.apply(&mut context.camera);
+        *context.bookmarks_mut() = header.bookmarks.clone();
+        Self {
+            ticks: recording.ticks.into_iter(),
+        }
+    }
+
+    pub(crate) fn next_tick(&mut self) -> Option<RecordedTick> {
+        self.ticks.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use glam::Vec3;
+    use winit::keyboard::KeyCode;
+
+    fn recording() -> Recording {
+        Recording {
+            header: RecordingHeader {
+                window_size: (640, 480),
+                camera: CameraView {
+                    eye: Vec3::new(0., 1., 3.),
+                    target: Vec3::ZERO,
+                    up: Vec3::Y,
+                    fovy: 1.,
+                    znear: 0.1,
+                    zfar: 100.,
+                    projection: Default::default(),
+                    reverse_z: false,
+                },
+                camera_mode: Some(CameraMode::Orbit),
+                bookmarks: CameraBookmarks::new(),
+            },
+            ticks: vec![
+                RecordedTick {
+                    timing: FrameTiming {
+                        dt: 0.016,
+                        frame: 0,
+                        time_delta: 16.,
+                        unix_time: 1_700_000_000.25,
+                    },
+                    redraw: true,
+                    backbuffer_size: (320, 240),
+                    events: vec![InputEvent::Key {
+                        code: KeyCode::KeyW,
+                        pressed: true,
+                        repeat: false,
+                    }],
+                },
+                RecordedTick {
+                    timing: FrameTiming {
+                        dt: 0.017,
+                        frame: 1,
+                        time_delta: 16.5,
+                        unix_time: 1_700_000_000.267,
+                    },
+                    redraw: false,
+                    backbuffer_size: (480, 360),
+                    events: vec![InputEvent::CursorMoved { x: 10., y: 20.5 }],
+                },
+            ],
+        }
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("vokselis-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        let path = temp_path("round-trip.jsonl");
+        let recording = recording();
+        recording.save(&path).unwrap();
+        let loaded = Recording::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), recording);
+    }
+
+    #[test]
+    fn load_drops_cut_off_tick() {
+        let path = temp_path("cut-off.jsonl");
+        recording().save(&path).unwrap();
+        let mut contents = std::fs::read_to_string(&path).unwrap();
+        contents.truncate(contents.len() - 10);
+        std::fs::write(&path, contents).unwrap();
+        let loaded = Recording::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().ticks, recording().ticks[..1]);
+    }
+}